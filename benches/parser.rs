@@ -0,0 +1,68 @@
+// Parser throughput benchmark: `cargo bench --bench parser`
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use plus::utils::parser::parse_plu_text;
+use std::hint::black_box;
+
+const COMMODITIES: [&str; 6] = ["Apple", "Melon", "Pear", "Citrus", "Tomato", "Potato"];
+const VARIETIES: [&str; 8] = [
+    "Akane",
+    "Braeburn",
+    "Cameo",
+    "Delblush",
+    "Empire",
+    "Fuji",
+    "Gala",
+    "Honeycrisp",
+];
+
+// Synthesizes a PLU document of roughly `lines` lines mixing every line shape the parser
+// understands: top-level headers, split sizes, alt names, sub-categories and 'o' items
+fn generate_document(lines: usize) -> String {
+    let mut doc = String::new();
+    let mut code = 3000;
+    let mut written = 0;
+    let mut commodity = 0;
+
+    while written < lines {
+        doc.push_str(COMMODITIES[commodity % COMMODITIES.len()]);
+        doc.push('\n');
+        written += 1;
+
+        for (i, variety) in VARIETIES.iter().enumerate() {
+            let line = match i % 4 {
+                0 => format!("• {}, small ({}), large ({})\n", variety, code, code + 1),
+                1 => format!("• {} / {} Red ({})\n", variety, variety, code),
+                2 => format!(
+                    "• {} [seedless, 3‐7 pounds] ({}, 4{}1)\n",
+                    variety, code, code
+                ),
+                _ => format!(
+                    "• {}:\n  o Mini ({})\n  o Jumbo ({})\n",
+                    variety,
+                    code,
+                    code + 1
+                ),
+            };
+            written += line.lines().count();
+            doc.push_str(&line);
+            code += 2;
+        }
+        commodity += 1;
+    }
+    doc
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_plu_text");
+    for lines in [100, 1_000] {
+        let doc = generate_document(lines);
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_function(format!("{}_lines", lines), |b| {
+            b.iter(|| parse_plu_text(black_box(&doc)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
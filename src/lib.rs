@@ -0,0 +1,3 @@
+// Library entry point so the models and parser can be reused outside the binary
+pub mod models;
+pub mod utils;
@@ -1,38 +1,21 @@
-// Declare the modules we created
-mod models;
-mod utils;
-
 // Import necessary items
+use plus::utils::parser::parse_plu_text; // Import the parser function
 use std::fs;
-use utils::parser::parse_plu_text; // Import the parser function
+use std::io::{self, IsTerminal, Read};
 
 fn main() {
     println!("Attempting to parse PLU data...");
 
-    // Define the path to the data file relative to the project root
-    let file_path = "plu_code/src/additional/plu.txt";
-
-    // Read the file content
-    let plu_text = match fs::read_to_string(file_path) {
-        Ok(text) => text,
-        Err(e) => {
-            eprintln!("Error reading file '{}': {}", file_path, e);
-            eprintln!(
-                "Please ensure the file exists at the correct location relative to where you run `cargo run`."
-            );
-            // Check if running from workspace root or plu_code directory
-            let alternative_path = "src/additional/plu.txt";
-            match fs::read_to_string(alternative_path) {
-                Ok(text) => {
-                    eprintln!("Trying alternative path '{}'", alternative_path);
-                    text
-                }
-                Err(e2) => {
-                    eprintln!("Error reading file '{}': {}", alternative_path, e2);
-                    std::process::exit(1); // Exit if file can't be read
-                }
-            }
-        }
+    // `plus -` reads stdin, as does a bare `plus` with piped input (`cat plu.txt | plus`);
+    // `plus <path>` reads that file, and a bare `plus` on a terminal uses the bundled data
+    let plu_text = match std::env::args().nth(1) {
+        Some(path) if path == "-" => read_stdin(),
+        Some(path) => fs::read_to_string(&path).unwrap_or_else(|e| {
+            eprintln!("Error reading file '{}': {}", path, e);
+            std::process::exit(1);
+        }),
+        None if !io::stdin().is_terminal() => read_stdin(),
+        None => read_default_file(),
     };
 
     // Call the parser function
@@ -52,11 +35,7 @@ fn main() {
             let apples: Vec<_> = collection
                 .items
                 .iter()
-                .filter(|item| {
-                    item.category_path
-                        .first()
-                        .map_or(false, |cat| cat == "Apple")
-                })
+                .filter(|item| item.category_path.first().is_some_and(|cat| cat == "Apple"))
                 .collect();
             println!("\n--- Found {} Apple Varieties ---", apples.len());
             if let Some(first_apple) = apples.first() {
@@ -95,3 +74,42 @@ fn main() {
         }
     }
 }
+
+// Reads the whole of stdin, exiting on failure
+fn read_stdin() -> String {
+    let mut text = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut text) {
+        eprintln!("Error reading stdin: {}", e);
+        std::process::exit(1);
+    }
+    text
+}
+
+// Reads the bundled data file, relative to the workspace root or the crate directory
+fn read_default_file() -> String {
+    // Define the path to the data file relative to the project root
+    let file_path = "plu_code/src/additional/plu.txt";
+
+    // Read the file content
+    match fs::read_to_string(file_path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error reading file '{}': {}", file_path, e);
+            eprintln!(
+                "Please ensure the file exists at the correct location relative to where you run `cargo run`."
+            );
+            // Check if running from workspace root or plu_code directory
+            let alternative_path = "src/additional/plu.txt";
+            match fs::read_to_string(alternative_path) {
+                Ok(text) => {
+                    eprintln!("Trying alternative path '{}'", alternative_path);
+                    text
+                }
+                Err(e2) => {
+                    eprintln!("Error reading file '{}': {}", alternative_path, e2);
+                    std::process::exit(1); // Exit if file can't be read
+                }
+            }
+        }
+    }
+}
@@ -1 +1,2 @@
+pub mod parse_report;
 pub mod plu_model;
@@ -0,0 +1,116 @@
+use crate::models::plu_model::PluCollection;
+use serde::{Deserialize, Serialize};
+
+/// A line the parser could not fully interpret, with the reason why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnprocessedLine {
+    /// 1-based line number in the input text.
+    pub line_number: usize,
+    /// The original line content.
+    pub line: String,
+    /// Human readable explanation, e.g. "malformed code tokens: abc"
+    pub reason: String,
+}
+
+/// Counters and diagnostics gathered while parsing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseStats {
+    /// Number of input lines read, including blank ones.
+    pub total_lines: usize,
+    /// Number of items produced.
+    pub items_parsed: usize,
+    /// Lines that were skipped or only partially understood.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unprocessed_lines: Vec<UnprocessedLine>,
+}
+
+/// Why the parser flagged a line while tracking the category hierarchy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarningReason {
+    /// A bullet item appeared before any top-level category.
+    OrphanItem { content: String },
+    /// The category path was deeper than the line's level allows and was trimmed.
+    PathTooDeep { path: Vec<String>, max_depth: usize },
+    /// A sub-item was found while the path did not have the expected length; it was skipped.
+    UnexpectedPathLength {
+        content: String,
+        path: Vec<String>,
+        expected: usize,
+    },
+    /// A line shaped like a top-level category had no items beneath it, e.g. a stray
+    /// "Lemon" between items; it was skipped and the current category kept.
+    CategoryWithoutItems { content: String },
+    /// A line matched no item or category pattern. It is also listed, with the same
+    /// line number, in `ParseStats::unprocessed_lines`.
+    UnmatchedLine { content: String },
+    /// A bare "[...]" line, most likely characteristics continuing the previous item's
+    /// line; it was skipped.
+    DetachedCharacteristics { content: String },
+    /// A split-size line produced fewer items than it has size groups, e.g. because one
+    /// group's codes could not be read.
+    SizeCountMismatch {
+        content: String,
+        sizes: usize,
+        items: usize,
+    },
+}
+
+/// A warning raised while parsing, tied to the 1-based input line it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    pub line_number: usize,
+    pub reason: WarningReason,
+}
+
+/// Everything produced by a parse run: the items plus the diagnostics about them.
+#[derive(Debug, Default)]
+pub struct ParseReport {
+    pub collection: PluCollection,
+    pub stats: ParseStats,
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl ParseStats {
+    // Records a line the parser skipped or could only salvage partially
+    pub(crate) fn record_unprocessed(&mut self, line_number: usize, line: &str, reason: &str) {
+        self.unprocessed_lines.push(UnprocessedLine {
+            line_number,
+            line: line.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+impl ParseReport {
+    /// Serializes the items together with the diagnostics as one pretty JSON document,
+    /// `{"items": [...], "stats": {...}, "warnings": [...]}` (keys in alphabetical order),
+    /// for CI dashboards that show both the data and its quality issues.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "items": self.collection.items,
+            "warnings": self.warnings,
+            "stats": self.stats,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_options::ParseOptions;
+    use crate::utils::parser::parse_plu_report;
+
+    #[test]
+    fn test_report_to_json() {
+        let text = "• Orphan (1234)\nApple\n• Akane, small (4098), large (4099)\n• ???";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        let json = report.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["items", "stats", "warnings"]);
+        assert_eq!(value["items"].as_array().unwrap().len(), 2);
+        assert_eq!(value["warnings"].as_array().unwrap().len(), 1);
+        assert_eq!(value["stats"]["total_lines"], 4);
+        assert_eq!(value["stats"]["unprocessed_lines"][0]["line_number"], 4);
+    }
+}
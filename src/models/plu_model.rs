@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
 
 /// Represents a specific product variety with its PLU codes and category.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+// Eq and Hash are implemented by hand because `composition` holds floats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PluItem {
     /// The specific name of the item, often including size or type.
     /// e.g., "Akane, small", "Mickey Lee", "Alfalfa Sprouts"
@@ -28,12 +31,178 @@ pub struct PluItem {
     /// Optional size description if explicitly mentioned (e.g., "small", "large")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<String>,
+
+    /// Letter suffix of a retailer variant code, kept separately from the numeric code.
+    /// e.g., "A" for "4011A" (whose numeric code 4011 lands in `plu_codes`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant_suffix: Option<String>,
+
+    /// Color word found at the start of the name, lowercased; the name keeps it too.
+    /// e.g., "red" for "Red Delicious"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+
+    /// Official commodity/variety name taken from a reference code table.
+    /// Filled in by `PluCollection::annotate_with_reference`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub official_name: Option<String>,
+
+    /// Whether this is an organic item: every code is a 5-digit code starting with 9
+    /// (e.g. 94011), or the name says "organic".
+    #[serde(default)]
+    pub is_organic: bool,
+
+    /// Set for draft entries whose code is a placeholder such as "(TBD)" or "(n/a)".
+    /// Such items keep their name and category but have no `plu_codes`.
+    #[serde(default)]
+    pub pending: bool,
+
+    /// Parts of a compound "A + B" name, e.g. ["Broccoli", "Cauliflower"] for
+    /// "Broccoli + Cauliflower blend". Empty for simple names; `name` keeps the full text.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+
+    /// Whether the source name carried a ™, ® or © mark; the marks are stripped from
+    /// `name` and `alternative_name`, e.g. "Pink Lady®" becomes "Pink Lady".
+    #[serde(default)]
+    pub trademarked: bool,
+
+    /// Percentage breakdown of a blend, e.g. [("romaine", 70.0), ("spinach", 30.0)]
+    /// for "Salad mix (70% romaine, 30% spinach)". The breakdown is removed from `name`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub composition: Vec<(String, f32)>,
+
+    /// Ripeness stage given as a trailing name segment, e.g. "green" for "Banana, green".
+    /// The segment is removed from `name`; a leading "Green ..." is a `color` instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ripeness: Option<String>,
+
+    /// Footnote numbers attached to the codes, from superscripts like "4011¹" or the
+    /// ",1,2" tail of "41361,2". Resolve them with `footnote_meanings`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub footnotes: Vec<u8>,
+
+    /// The codes exactly as written, kept only when numeric parsing would lose something,
+    /// e.g. ["0401"] for the code 401.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub code_strings: Vec<String>,
+
+    /// Further aliases beyond `alternative_name`: "Rosa" for "Aurora / Southern Rose / Rosa",
+    /// or synonyms attached by `PluCollection::apply_synonyms` ("courgette" for "Zucchini").
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternative_names: Vec<String>,
+
+    /// Pricing unit from a "per lb" or "/lb" annotation, normalized to "lb", "kg" or "each".
+    /// The annotation is removed from `name`; weight ranges like "3-7 pounds" stay
+    /// characteristics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sold_by: Option<String>,
+
+    /// Code blocks written as ranges in the item's code group, e.g. 4193-4217 for
+    /// "Reserved (4193-4217)". Range bounds are not listed in `plu_codes`, so
+    /// "(4098, 4100–4105)" gives `plu_codes` [4098] and the range 4100-4105 here.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub code_ranges: Vec<CodeRange>,
+}
+
+/// An inclusive block of PLU codes, e.g. the retailer assigned 4193-4217.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CodeRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl CodeRange {
+    /// Whether `code` falls inside this range (both ends included).
+    pub fn contains(&self, code: u32) -> bool {
+        (self.start..=self.end).contains(&code)
+    }
+}
+
+/// A read-only view of a parsed item whose strings borrow from the parsed text,
+/// as returned by `parse_plu_text_borrowed`.
+///
+/// Every `&'a str` is a slice of the input buffer, so the items cannot outlive it:
+/// keep the text alive (and unmodified) for as long as the items are in use, or
+/// convert them with `PluItemRef::to_item` first.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct PluItemRef<'a> {
+    pub name: &'a str,
+    pub plu_codes: Vec<u32>,
+    pub category_path: Vec<&'a str>,
+    pub alternative_name: Option<&'a str>,
+    pub size: Option<&'a str>,
+    /// Whether the item is one of several size groups on a single line, as in
+    /// "Akane, small (4098), large (4099)". `parse_plu_text` appends the size to the
+    /// names of such items, which `to_item` does as well.
+    pub split_sizes: bool,
+}
+
+impl PluItemRef<'_> {
+    /// Copies the borrowed strings into an owned `PluItem`, named the way
+    /// `parse_plu_text` names it, e.g. "Akane, small" for a split size group.
+    pub fn to_item(&self) -> PluItem {
+        let sized = |name: &str| match self.size {
+            Some(size) if self.split_sizes => format!("{name}, {size}"),
+            _ => name.to_string(),
+        };
+        PluItem::new(
+            sized(self.name),
+            self.plu_codes.clone(),
+            self.category_path.iter().map(|c| c.to_string()).collect(),
+            self.alternative_name.map(sized),
+            Vec::new(),
+            self.size.map(str::to_string),
+        )
+    }
+}
+
+/// One PLU code prepared for GS1 DataBar encoding, as used for random-weight produce.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataBarEntry {
+    pub code: u32,
+    /// Name of the item the code belongs to.
+    pub name: String,
+    /// The code zero-padded into a GTIN-14 with its GS1 check digit,
+    /// e.g. "00000000040112" for 4011.
+    pub gtin14: String,
+}
+
+impl DataBarEntry {
+    /// The GS1 element string encoded in the bar, e.g. "(01)00000000040112".
+    pub fn element_string(&self) -> String {
+        format!("(01){}", self.gtin14)
+    }
+}
+
+/// Items added, removed and changed between two versions of a collection.
+/// Items are matched by their codes (or by `PluItem::id` when they have none).
+#[derive(Debug, Default, PartialEq)]
+pub struct CollectionDiff<'a> {
+    pub added: Vec<&'a PluItem>,
+    pub removed: Vec<&'a PluItem>,
+    /// `(previous, current)` pairs with the same codes but different content.
+    pub changed: Vec<(&'a PluItem, &'a PluItem)>,
 }
 
 /// Holds the collection of all parsed PLU items.
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct PluCollection {
     pub items: Vec<PluItem>,
+
+    /// Code blocks reserved for retailer assignment, e.g. "retailer assigned (4193-4217)".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retailer_ranges: Vec<CodeRange>,
+
+    /// Footnote meanings from the document's legend, e.g. {1: "Retailer assigned"}
+    /// for a "¹ Retailer assigned." line. Used by `PluCollection::footnote_meanings`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub footnote_legend: HashMap<u8, String>,
+
+    /// Revision of the PLU list, taken from a title line such as "IFPS PLU Codes 2024"
+    /// before the first category, e.g. "2024".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
 }
 
 // Optional helper for creating items more easily during parsing
@@ -46,6 +215,8 @@ impl PluItem {
         characteristics: Vec<String>,
         size: Option<String>,
     ) -> Self {
+        let is_organic =
+            !plu_codes.is_empty() && plu_codes.iter().all(|&code| is_organic_code(code));
         PluItem {
             name,
             plu_codes,
@@ -53,6 +224,1764 @@ impl PluItem {
             alternative_name,
             characteristics,
             size,
+            variant_suffix: None,
+            color: None,
+            official_name: None,
+            is_organic,
+            pending: false,
+            components: Vec::new(),
+            trademarked: false,
+            composition: Vec::new(),
+            ripeness: None,
+            footnotes: Vec::new(),
+            code_strings: Vec::new(),
+            alternative_names: Vec::new(),
+            sold_by: None,
+            code_ranges: Vec::new(),
+        }
+    }
+
+    /// The variety name without its size suffix.
+    /// e.g., "Akane" for an item named "Akane, small" with size "small"
+    pub fn canonical_name(&self) -> &str {
+        match &self.size {
+            Some(size) => self
+                .name
+                .strip_suffix(size.as_str())
+                .and_then(|rest| rest.trim_end().strip_suffix(','))
+                .map_or(self.name.as_str(), str::trim_end),
+            None => &self.name,
+        }
+    }
+
+    /// Whether the item stands only for code ranges, such as a reserved block,
+    /// with no discrete codes of its own.
+    pub fn is_range_only(&self) -> bool {
+        self.plu_codes.is_empty() && !self.code_ranges.is_empty()
+    }
+
+    /// Number of PLU codes on this item.
+    pub fn code_count(&self) -> usize {
+        self.plu_codes.len()
+    }
+
+    /// Whether the item maps to more than one PLU code, e.g. cantaloupe with 4049 and 43181.
+    pub fn has_multiple_codes(&self) -> bool {
+        self.code_count() > 1
+    }
+
+    /// The category path followed by the item name, joined with " > ".
+    /// e.g., "Melon > Watermelon > Mini, seedless"
+    pub fn breadcrumb(&self) -> String {
+        self.category_path
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.name.as_str()))
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+
+    /// Resolves this item's footnote numbers through a document legend,
+    /// e.g. {1: "Canada only"}. Footnotes missing from the legend are skipped.
+    pub fn footnote_meanings<'a>(&self, legend: &'a HashMap<u8, String>) -> Vec<&'a str> {
+        self.footnotes
+            .iter()
+            .filter_map(|footnote| legend.get(footnote))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Builds one item per `(size, code)` pair the way the parser splits
+    /// "Akane, small (4098), large (4099)": names become "Akane, small", "Akane, large".
+    pub fn with_sizes(
+        base_name: &str,
+        category: Vec<String>,
+        sizes: &[(&str, u32)],
+    ) -> Vec<PluItem> {
+        sizes
+            .iter()
+            .map(|&(size, code)| {
+                let size = size.trim().to_lowercase();
+                PluItem::new(
+                    format!("{}, {}", base_name.trim(), size),
+                    vec![code],
+                    category.clone(),
+                    None,
+                    Vec::new(),
+                    Some(size),
+                )
+            })
+            .collect()
+    }
+
+    /// Barcode-ready strings for each code: 4-digit codes are zero-padded ("0123"), and an
+    /// organic item's 4-digit codes get the "9" prefix ("4011" -> "94011").
+    /// 5-digit codes are already complete and are returned as-is.
+    pub fn barcode_strings(&self) -> Vec<String> {
+        self.plu_codes
+            .iter()
+            .map(|&code| {
+                if code > 9999 {
+                    code.to_string()
+                } else if self.is_organic {
+                    format!("9{:04}", code)
+                } else {
+                    format!("{:04}", code)
+                }
+            })
+            .collect()
+    }
+
+    /// Deterministic id built from the category path, canonical name and size,
+    /// e.g. "apple/akane/small". The same input always yields the same id.
+    pub fn id(&self) -> String {
+        self.category_path
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.canonical_name()))
+            .chain(self.size.as_deref())
+            .map(slugify)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Lowercases and trims every characteristic and drops the duplicates,
+    /// keeping the first occurrence's position, e.g. ["seedless", "Seedless "] -> ["seedless"].
+    pub fn normalize_characteristics(&mut self) {
+        let mut seen = Vec::new();
+        for characteristic in self.characteristics.drain(..) {
+            let characteristic = characteristic.trim().to_lowercase();
+            if !characteristic.is_empty() && !seen.contains(&characteristic) {
+                seen.push(characteristic);
+            }
+        }
+        self.characteristics = seen;
+    }
+}
+
+impl PluCollection {
+    /// Counts PLU codes grouped by their first `digits` digits.
+    /// e.g., with `digits = 2`, codes 4098 and 4099 both count towards prefix 40.
+    /// Codes shorter than `digits` are grouped under the whole code.
+    pub fn codes_by_prefix(&self, digits: usize) -> BTreeMap<u32, usize> {
+        let mut counts = BTreeMap::new();
+        for code in self.items.iter().flat_map(|item| item.plu_codes.iter()) {
+            let code_str = code.to_string();
+            let prefix_len = digits.min(code_str.len());
+            if let Ok(prefix) = code_str[..prefix_len].parse::<u32>() {
+                *counts.entry(prefix).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Scores every item against `query` and returns the matches sorted by descending score.
+    /// Exact matches score highest, then prefix, substring and finally fuzzy matches.
+    /// Both `name` and `alternative_name` are considered, case-insensitively.
+    pub fn ranked_search(&self, query: &str) -> Vec<(&PluItem, f32)> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut results: Vec<(&PluItem, f32)> = self
+            .items
+            .iter()
+            .filter_map(|item| {
+                let best = std::iter::once(&item.name)
+                    .chain(item.alternative_name.iter())
+                    .map(|candidate| match_score(&query, &candidate.to_lowercase()))
+                    .fold(0.0_f32, f32::max);
+                (best > 0.0).then_some((item, best))
+            })
+            .collect();
+
+        // Stable sort keeps parse order for equally scored items
+        results.sort_by(|a, b| b.1.total_cmp(&a.1));
+        results
+    }
+
+    /// Serializes the collection as pretty JSON with items sorted by codes, then name.
+    /// The output is byte-stable regardless of the order items were parsed in,
+    /// which keeps snapshot files diff-friendly.
+    pub fn to_json_sorted(&self) -> Result<String, serde_json::Error> {
+        let mut items: Vec<&PluItem> = self.items.iter().collect();
+        items.sort_by(|a, b| {
+            a.plu_codes
+                .cmp(&b.plu_codes)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        serde_json::to_string_pretty(&serde_json::json!({ "items": items }))
+    }
+
+    /// Returns the union of codes across every size of the variety called `name`.
+    /// Matching is done on the canonical (size-less) name, case-insensitively.
+    /// The result is sorted and free of duplicates.
+    pub fn codes_for_variety(&self, name: &str) -> Vec<u32> {
+        let mut codes: Vec<u32> = self
+            .items
+            .iter()
+            .filter(|item| item.canonical_name().eq_ignore_ascii_case(name.trim()))
+            .flat_map(|item| item.plu_codes.iter().copied())
+            .collect();
+        codes.sort_unstable();
+        codes.dedup();
+        codes
+    }
+
+    /// Maps each category path depth to the number of items at that depth.
+    /// Useful for checking the parser produced the expected tree shape.
+    pub fn depth_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for item in &self.items {
+            *histogram.entry(item.category_path.len()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// The deepest category path found in the collection (0 when empty).
+    pub fn max_depth(&self) -> usize {
+        self.items
+            .iter()
+            .map(|item| item.category_path.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Fills in `official_name` from a reference table of code -> official name.
+    /// The first of an item's codes found in the table wins; items without a match are left untouched.
+    pub fn annotate_with_reference(&mut self, reference: &HashMap<u32, String>) {
+        for item in &mut self.items {
+            if let Some(name) = item.plu_codes.iter().find_map(|code| reference.get(code)) {
+                item.official_name = Some(name.clone());
+            }
+        }
+    }
+
+    /// Whether `code` sits inside any captured retailer-reserved range.
+    /// Handy for POS tools to avoid assigning a store code inside a reserved block.
+    pub fn is_reserved(&self, code: u32) -> bool {
+        self.retailer_ranges
+            .iter()
+            .any(|range| range.contains(code))
+    }
+
+    /// Splits the collection into (organic, conventional) collections.
+    /// Retailer ranges and the footnote legend apply to both halves and are copied into each.
+    pub fn partition_organic(&self) -> (PluCollection, PluCollection) {
+        let (organic, conventional): (Vec<PluItem>, Vec<PluItem>) =
+            self.items.iter().cloned().partition(|item| item.is_organic);
+        let mut organic: PluCollection = organic.into_iter().collect();
+        let mut conventional: PluCollection = conventional.into_iter().collect();
+        organic.retailer_ranges = self.retailer_ranges.clone();
+        conventional.retailer_ranges = self.retailer_ranges.clone();
+        organic.footnote_legend = self.footnote_legend.clone();
+        conventional.footnote_legend = self.footnote_legend.clone();
+        organic.revision = self.revision.clone();
+        conventional.revision = self.revision.clone();
+        (organic, conventional)
+    }
+
+    /// Deterministic hash of the collection's content (items, retailer ranges, footnote
+    /// legend and revision), independent of item order. Two parses of reordered but
+    /// otherwise identical input hash the same.
+    pub fn content_hash(&self) -> u64 {
+        let mut item_hashes: Vec<u64> = self.items.iter().map(fnv_hash).collect();
+        item_hashes.sort_unstable();
+        let mut range_hashes: Vec<u64> = self.retailer_ranges.iter().map(fnv_hash).collect();
+        range_hashes.sort_unstable();
+        let legend: BTreeMap<&u8, &String> = self.footnote_legend.iter().collect();
+        fnv_hash(&(item_hashes, range_hashes, legend, &self.revision))
+    }
+
+    /// Iterates over every item's fully qualified name (its breadcrumb).
+    pub fn qualified_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.items.iter().map(PluItem::breadcrumb)
+    }
+
+    /// Items whose name is empty or whitespace, e.g. after over-eager stripping of
+    /// sizes, characteristics or alternative names.
+    pub fn find_nameless(&self) -> Vec<&PluItem> {
+        self.items
+            .iter()
+            .filter(|item| item.name.trim().is_empty())
+            .collect()
+    }
+
+    /// Pairs each item with every one of its codes inside `start..=end`.
+    /// Unlike `is_reserved`, this looks at the items' own codes, not retailer ranges.
+    pub fn codes_in_range(&self, start: u32, end: u32) -> Vec<(&PluItem, u32)> {
+        self.items
+            .iter()
+            .flat_map(|item| {
+                item.plu_codes
+                    .iter()
+                    .filter(move |&&code| (start..=end).contains(&code))
+                    .map(move |&code| (item, code))
+            })
+            .collect()
+    }
+
+    /// One `(code, item name, category path)` tuple per code, in item order.
+    /// The minimal CSV-like shape many downstream scripts want.
+    pub fn flatten(&self) -> Vec<(u32, String, Vec<String>)> {
+        self.items
+            .iter()
+            .flat_map(|item| {
+                item.plu_codes
+                    .iter()
+                    .map(move |&code| (code, item.name.clone(), item.category_path.clone()))
+            })
+            .collect()
+    }
+
+    /// Checks that neighbouring size variants of one line, e.g. "Akane, small" and
+    /// "Akane, large", landed under the same category path.
+    /// Returns every adjacent pair with matching canonical names but differing paths.
+    pub fn validate_size_groups(&self) -> Result<(), Vec<(&PluItem, &PluItem)>> {
+        let mismatches: Vec<_> = self
+            .items
+            .windows(2)
+            .filter(|pair| {
+                pair[0].size.is_some()
+                    && pair[1].size.is_some()
+                    && pair[0].canonical_name() == pair[1].canonical_name()
+                    && pair[0].category_path != pair[1].category_path
+            })
+            .map(|pair| (&pair[0], &pair[1]))
+            .collect();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+
+    /// Every `(code, item)` pair whose code starts with the digits in `prefix`, sorted by code.
+    /// Meant for type-ahead on numeric entry, e.g. "401" while the user types "4011".
+    pub fn codes_with_prefix(&self, prefix: &str) -> Vec<(u32, &PluItem)> {
+        let mut matches: Vec<(u32, &PluItem)> = self
+            .items
+            .iter()
+            .flat_map(|item| item.plu_codes.iter().map(move |&code| (code, item)))
+            .filter(|(code, _)| code.to_string().starts_with(prefix))
+            .collect();
+        matches.sort_by_key(|&(code, _)| code);
+        matches
+    }
+
+    /// Items without an `alternative_name` whose code has one in `reference` (code -> alias),
+    /// paired with that alias. Surfaces aliases the parser missed.
+    pub fn missing_alt_names<'a>(
+        &'a self,
+        reference: &'a HashMap<u32, String>,
+    ) -> Vec<(&'a PluItem, &'a str)> {
+        self.items
+            .iter()
+            .filter(|item| item.alternative_name.is_none())
+            .filter_map(|item| {
+                item.plu_codes
+                    .iter()
+                    .find_map(|code| reference.get(code))
+                    .map(|alias| (item, alias.as_str()))
+            })
+            .collect()
+    }
+
+    /// The whole collection as a `serde_json::Value`, for callers that edit JSON dynamically
+    /// without a serialize-then-reparse round trip.
+    pub fn to_value(&self) -> serde_json::Value {
+        // Only strings, numbers, bools and sequences are involved, so this cannot fail
+        serde_json::to_value(self).expect("PluCollection serializes to JSON")
+    }
+
+    /// Codes found under more than one distinct category path, with those paths in
+    /// first-seen order. Usually a parse or source error. Sorted by code.
+    pub fn cross_category_codes(&self) -> Vec<(u32, Vec<Vec<String>>)> {
+        let mut paths_by_code: BTreeMap<u32, Vec<Vec<String>>> = BTreeMap::new();
+        for item in &self.items {
+            for &code in &item.plu_codes {
+                let paths = paths_by_code.entry(code).or_default();
+                if !paths.contains(&item.category_path) {
+                    paths.push(item.category_path.clone());
+                }
+            }
+        }
+        paths_by_code
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .collect()
+    }
+
+    /// Items filed under `category` at any level of their path, compared case-insensitively.
+    /// e.g. "Melon" and "Watermelon" both match "Mickey Lee".
+    pub fn by_category(&self, category: &str) -> Vec<&PluItem> {
+        self.items
+            .iter()
+            .filter(|item| in_category(item, category))
+            .collect()
+    }
+
+    /// Items filed under any of `categories` (OR), matched like in `by_category`.
+    pub fn by_categories(&self, categories: &[&str]) -> Vec<&PluItem> {
+        self.items
+            .iter()
+            .filter(|item| {
+                categories
+                    .iter()
+                    .any(|category| in_category(item, category))
+            })
+            .collect()
+    }
+
+    /// Items whose path contains every one of `categories` (AND), matched like in `by_category`.
+    pub fn by_all_categories(&self, categories: &[&str]) -> Vec<&PluItem> {
+        self.items
+            .iter()
+            .filter(|item| {
+                categories
+                    .iter()
+                    .all(|category| in_category(item, category))
+            })
+            .collect()
+    }
+
+    /// Codes found in `cat_a` but in no item of `cat_b`, sorted and free of duplicates.
+    /// Categories are matched like in `by_category`.
+    pub fn codes_diff(&self, cat_a: &str, cat_b: &str) -> Vec<u32> {
+        let codes_of = |category| -> BTreeSet<u32> {
+            self.by_category(category)
+                .into_iter()
+                .flat_map(|item| item.plu_codes.iter().copied())
+                .collect()
+        };
+        codes_of(cat_a)
+            .difference(&codes_of(cat_b))
+            .copied()
+            .collect()
+    }
+
+    /// Resolves `item`'s footnotes through the legend parsed from the document.
+    pub fn footnote_meanings(&self, item: &PluItem) -> Vec<&str> {
+        item.footnote_meanings(&self.footnote_legend)
+    }
+
+    /// Compact code -> (canonical name, size) map for scale programming,
+    /// e.g. 4098 -> ("Akane", Some("small")).
+    /// Every code of a multi-code item gets its own entry; when a code appears on
+    /// several items the first one in document order wins.
+    pub fn to_scale_map(&self) -> BTreeMap<u32, (String, Option<String>)> {
+        let mut map = BTreeMap::new();
+        for item in &self.items {
+            for &code in &item.plu_codes {
+                map.entry(code)
+                    .or_insert_with(|| (item.canonical_name().to_string(), item.size.clone()));
+            }
+        }
+        map
+    }
+
+    /// Rust source for a `PLU_CODES: &[(u32, &str)]` static of code -> name, sorted by code
+    /// so generated tables can be binary searched. When a code appears on several items
+    /// the first one in document order wins.
+    pub fn to_rust_array(&self) -> String {
+        let mut names = BTreeMap::new();
+        for item in &self.items {
+            for &code in &item.plu_codes {
+                names.entry(code).or_insert(item.name.as_str());
+            }
+        }
+        let mut source = String::from("pub static PLU_CODES: &[(u32, &str)] = &[\n");
+        for (code, name) in names {
+            // Debug formatting yields a correctly escaped string literal
+            source.push_str(&format!("    ({}, {:?}),\n", code, name));
+        }
+        source.push_str("];\n");
+        source
+    }
+
+    /// The item with the longest `plu_codes`; ties go to the first one in document order.
+    pub fn item_with_most_codes(&self) -> Option<&PluItem> {
+        self.items.iter().reduce(|best, item| {
+            if item.code_count() > best.code_count() {
+                item
+            } else {
+                best
+            }
+        })
+    }
+
+    /// One DataBar entry per code, in item order. This is a pure data transformation;
+    /// rendering the actual barcode is left to the caller.
+    pub fn to_databar_list(&self) -> Vec<DataBarEntry> {
+        self.items
+            .iter()
+            .flat_map(|item| {
+                item.plu_codes.iter().map(|&code| DataBarEntry {
+                    code,
+                    name: item.name.clone(),
+                    gtin14: gtin14(code),
+                })
+            })
+            .collect()
+    }
+
+    /// Distinct top-level categories in first-seen order, e.g. for a category menu.
+    pub fn top_categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = Vec::new();
+        for category in self
+            .items
+            .iter()
+            .filter_map(|item| item.category_path.first())
+        {
+            if !categories.contains(&category.as_str()) {
+                categories.push(category);
+            }
+        }
+        categories
+    }
+
+    /// Codes whose digit count differs from the usual one in their top-level category,
+    /// e.g. a 5-digit "43181" among 4-digit melon codes, which often means a footnote digit
+    /// was glued to the code. Organic (9xxxx) and GMO (8xxxx) prefixed codes are expected to
+    /// be 5 digits and are ignored. Ties in the norm go to the shorter length.
+    pub fn length_anomalies(&self) -> Vec<(&PluItem, u32)> {
+        let digits = |code: u32| code.to_string().len();
+        let is_prefixed = |code: u32| (80000..=99999).contains(&code);
+        let category_of = |item: &PluItem| item.category_path.first().cloned().unwrap_or_default();
+
+        let mut length_counts: HashMap<String, BTreeMap<usize, usize>> = HashMap::new();
+        for item in &self.items {
+            for &code in item.plu_codes.iter().filter(|&&code| !is_prefixed(code)) {
+                *length_counts
+                    .entry(category_of(item))
+                    .or_default()
+                    .entry(digits(code))
+                    .or_default() += 1;
+            }
+        }
+        // max_by_key keeps the last of equal counts, so walk longest first to favour the shortest
+        let norms: HashMap<String, usize> = length_counts
+            .into_iter()
+            .filter_map(|(category, counts)| {
+                let norm = counts.into_iter().rev().max_by_key(|&(_, count)| count)?.0;
+                Some((category, norm))
+            })
+            .collect();
+
+        self.items
+            .iter()
+            .flat_map(|item| {
+                let norm = norms.get(&category_of(item)).copied();
+                item.plu_codes
+                    .iter()
+                    .filter(move |&&code| !is_prefixed(code) && norm != Some(digits(code)))
+                    .map(move |&code| (item, code))
+            })
+            .collect()
+    }
+
+    /// Items keyed by the uppercase first letter of their name for A-Z navigation.
+    /// Names starting with anything but a letter go into the '#' bucket.
+    pub fn group_alphabetical(&self) -> BTreeMap<char, Vec<&PluItem>> {
+        let mut groups: BTreeMap<char, Vec<&PluItem>> = BTreeMap::new();
+        for item in &self.items {
+            let key = item
+                .name
+                .trim_start()
+                .chars()
+                .next()
+                .filter(|c| c.is_alphabetic())
+                .and_then(|c| c.to_uppercase().next())
+                .unwrap_or('#');
+            groups.entry(key).or_default().push(item);
+        }
+        groups
+    }
+
+    /// Compares this collection against an earlier version of it.
+    /// Added items keep this collection's order, removed ones the previous order.
+    /// Items listed more than once under the same codes are matched in order, so a
+    /// dropped duplicate shows up as removed.
+    pub fn diff<'a>(&'a self, previous: &'a PluCollection) -> CollectionDiff<'a> {
+        let key = |item: &PluItem| {
+            if item.plu_codes.is_empty() {
+                item.id()
+            } else {
+                format_codes(&item.plu_codes)
+            }
+        };
+        // Items sharing a key, such as duplicate listings, are paired up in document order
+        let mut previous_by_key: HashMap<String, Vec<&PluItem>> = HashMap::new();
+        for item in &previous.items {
+            previous_by_key.entry(key(item)).or_default().push(item);
+        }
+        let mut current_counts: HashMap<String, usize> = HashMap::new();
+
+        let mut diff = CollectionDiff::default();
+        for item in &self.items {
+            let nth = current_counts.entry(key(item)).or_default();
+            let old = previous_by_key
+                .get(&key(item))
+                .and_then(|olds| olds.get(*nth));
+            *nth += 1;
+            match old {
+                None => diff.added.push(item),
+                Some(&old) if old != item => diff.changed.push((old, item)),
+                Some(_) => {}
+            }
+        }
+        let mut previous_counts: HashMap<String, usize> = HashMap::new();
+        for item in &previous.items {
+            let nth = previous_counts.entry(key(item)).or_default();
+            if *nth >= current_counts.get(&key(item)).copied().unwrap_or(0) {
+                diff.removed.push(item);
+            }
+            *nth += 1;
+        }
+        diff
+    }
+
+    /// Human-readable summary of `diff` against `previous`, one line per change:
+    /// "+ Added Akane (4098)", "- Removed Gala (4133)", "~ Changed size of Akane (4098): small -> large".
+    pub fn changelog(&self, previous: &PluCollection) -> String {
+        let diff = self.diff(previous);
+        let label = |item: &PluItem| format!("{} ({})", item.name, format_codes(&item.plu_codes));
+        let mut lines = Vec::new();
+        for item in &diff.added {
+            lines.push(format!("+ Added {}", label(item)));
+        }
+        for item in &diff.removed {
+            lines.push(format!("- Removed {}", label(item)));
+        }
+        for (old, new) in &diff.changed {
+            let show = |value: Option<&str>| value.unwrap_or("none").to_string();
+            let fields = [
+                ("name", show(Some(&old.name)), show(Some(&new.name))),
+                (
+                    "alternative name",
+                    show(old.alternative_name.as_deref()),
+                    show(new.alternative_name.as_deref()),
+                ),
+                ("size", show(old.size.as_deref()), show(new.size.as_deref())),
+                (
+                    "category",
+                    old.category_path.join(" > "),
+                    new.category_path.join(" > "),
+                ),
+            ];
+            let before = lines.len();
+            for (field, from, to) in fields.into_iter().filter(|(_, from, to)| from != to) {
+                lines.push(format!(
+                    "~ Changed {} of {}: {} -> {}",
+                    field,
+                    label(old),
+                    from,
+                    to
+                ));
+            }
+            if lines.len() == before {
+                lines.push(format!("~ Changed {}", label(old)));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Other items with exactly the same `category_path` as `item`, for "related items" lists.
+    /// `item` itself (or any identical copy of it) is left out.
+    pub fn siblings(&self, item: &PluItem) -> Vec<&PluItem> {
+        self.items
+            .iter()
+            .filter(|other| other.category_path == item.category_path && *other != item)
+            .collect()
+    }
+
+    /// Attaches known synonyms to `alternative_names`, e.g. {"zucchini": ["courgette"]}.
+    /// An item matches an entry when its canonical name or `alternative_name` equals the key
+    /// or one of the synonyms (ignoring case); it then gets every other word of the entry.
+    /// Names the item already carries are not repeated. Entries are applied in key order,
+    /// so an item matching several of them always gets its names in the same order.
+    pub fn apply_synonyms(&mut self, synonyms: &HashMap<String, Vec<String>>) {
+        let mut entries: Vec<(&String, &Vec<String>)> = synonyms.iter().collect();
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        for item in &mut self.items {
+            let known: Vec<String> = std::iter::once(item.canonical_name())
+                .chain(item.alternative_name.as_deref())
+                .map(str::to_lowercase)
+                .collect();
+            for &(key, words) in &entries {
+                let entry: Vec<&String> = std::iter::once(key).chain(words).collect();
+                if !entry
+                    .iter()
+                    .any(|word| known.contains(&word.to_lowercase()))
+                {
+                    continue;
+                }
+                for word in entry {
+                    let already_named = known.contains(&word.to_lowercase())
+                        || item
+                            .alternative_names
+                            .iter()
+                            .any(|name| name.eq_ignore_ascii_case(word));
+                    if !already_named {
+                        item.alternative_names.push(word.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Collapses items that are identical apart from their footnotes into the first of them,
+    /// which receives the sorted union of their footnotes. Footnote-free duplicates are merged
+    /// too; items differing in anything else are kept apart.
+    pub fn merge_footnote_variants(&mut self) {
+        // Footnotes are set aside while grouping, so items can be bucketed by hash and
+        // compared in place
+        let mut merged: Vec<PluItem> = Vec::with_capacity(self.items.len());
+        let mut footnotes: Vec<Vec<u8>> = Vec::with_capacity(self.items.len());
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        for mut item in self.items.drain(..) {
+            let item_footnotes = std::mem::take(&mut item.footnotes);
+            let bucket = buckets.entry(fnv_hash(&item)).or_default();
+            match bucket.iter().find(|&&kept| merged[kept] == item) {
+                Some(&kept) => {
+                    let kept = &mut footnotes[kept];
+                    kept.extend(item_footnotes);
+                    kept.sort_unstable();
+                    kept.dedup();
+                }
+                None => {
+                    bucket.push(merged.len());
+                    merged.push(item);
+                    footnotes.push(item_footnotes);
+                }
+            }
+        }
+        for (item, item_footnotes) in merged.iter_mut().zip(footnotes) {
+            item.footnotes = item_footnotes;
+        }
+        self.items = merged;
+    }
+
+    /// Maps each lowercase word of the items' names and alternative names to the items
+    /// using it, e.g. "delicious" -> [Golden Delicious, Red Delicious], for multi-word search.
+    /// Words are split on anything but letters and digits; each item is listed once per word.
+    pub fn word_index(&self) -> HashMap<String, Vec<&PluItem>> {
+        let mut index: HashMap<String, Vec<&PluItem>> = HashMap::new();
+        for item in &self.items {
+            let words: BTreeSet<String> = std::iter::once(item.name.as_str())
+                .chain(item.alternative_name.as_deref())
+                .chain(item.alternative_names.iter().map(String::as_str))
+                .flat_map(|name| name.split(|c: char| !c.is_alphanumeric()))
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase)
+                .collect();
+            for word in words {
+                index.entry(word).or_default().push(item);
+            }
         }
+        index
+    }
+
+    /// Number of distinct codes under each top-level category, e.g. {"Apple": 4}.
+    /// Items without a category are left out.
+    pub fn category_code_counts(&self) -> BTreeMap<String, usize> {
+        let mut codes: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+        for item in &self.items {
+            if let Some(category) = item.category_path.first() {
+                codes
+                    .entry(category.clone())
+                    .or_default()
+                    .extend(&item.plu_codes);
+            }
+        }
+        codes
+            .into_iter()
+            .map(|(category, codes)| (category, codes.len()))
+            .collect()
+    }
+
+    /// The sizes used in the collection, each once, for size filters: the standard sizes
+    /// from smallest to largest ("small", "medium", "large", "extra large", "jumbo"),
+    /// then any others alphabetically.
+    pub fn distinct_sizes(&self) -> Vec<String> {
+        const ORDER: [&str; 5] = ["small", "medium", "large", "extra large", "jumbo"];
+        let mut sizes: Vec<String> = self
+            .items
+            .iter()
+            .filter_map(|item| item.size.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        sizes.sort_by_key(|size| ORDER.iter().position(|s| s == size).unwrap_or(ORDER.len()));
+        sizes
+    }
+
+    /// The shortest leading digits that tell each code apart from every other code in the
+    /// collection, for quick keypad entry: {4011: "4011", 4012: "4012", 3421: "3"}.
+    /// A code that is itself the start of a longer code gets its full digits.
+    pub fn unique_prefixes(&self) -> HashMap<u32, String> {
+        let codes: BTreeSet<u32> = self
+            .items
+            .iter()
+            .flat_map(|i| i.plu_codes.iter().copied())
+            .collect();
+        let digits: Vec<String> = codes.iter().map(u32::to_string).collect();
+        codes
+            .iter()
+            .zip(&digits)
+            .map(|(&code, code_digits)| {
+                let len = (1..=code_digits.len())
+                    .find(|&len| {
+                        let prefix = &code_digits[..len];
+                        digits
+                            .iter()
+                            .all(|other| other == code_digits || !other.starts_with(prefix))
+                    })
+                    .unwrap_or(code_digits.len());
+                (code, code_digits[..len].to_string())
+            })
+            .collect()
+    }
+}
+
+// Whether `category` appears at any level of the item's path, ignoring case
+fn in_category(item: &PluItem, category: &str) -> bool {
+    item.category_path
+        .iter()
+        .any(|level| level.eq_ignore_ascii_case(category))
+}
+
+// Scores a lowercase query against a lowercase candidate (0.0 means no match)
+fn match_score(query: &str, candidate: &str) -> f32 {
+    if candidate == query {
+        1.0
+    } else if candidate.starts_with(query) {
+        0.75
+    } else if candidate.contains(query) {
+        0.5
+    } else {
+        // Fuzzy: compare against the whole candidate and each of its words
+        let similarity = std::iter::once(candidate)
+            .chain(candidate.split(|c: char| !c.is_alphanumeric()))
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let max_len = query.chars().count().max(word.chars().count());
+                1.0 - levenshtein(query, word) as f32 / max_len as f32
+            })
+            .fold(0.0_f32, f32::max);
+        // Anything below this similarity is noise rather than a typo
+        if similarity >= 0.6 {
+            0.25 * similarity
+        } else {
+            0.0
+        }
+    }
+}
+
+// Classic edit distance between two strings, counted in chars
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b_chars.len()]
+}
+
+// Percentages are parsed from finite numbers and never NaN, so equality is total
+impl Eq for PluItem {}
+
+impl Hash for PluItem {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Destructured without `..` so a new field can't be left out of the hash
+        let PluItem {
+            name,
+            plu_codes,
+            category_path,
+            alternative_name,
+            characteristics,
+            size,
+            variant_suffix,
+            color,
+            official_name,
+            is_organic,
+            pending,
+            components,
+            trademarked,
+            composition,
+            ripeness,
+            footnotes,
+            code_strings,
+            alternative_names,
+            sold_by,
+            code_ranges,
+        } = self;
+        name.hash(state);
+        plu_codes.hash(state);
+        category_path.hash(state);
+        alternative_name.hash(state);
+        characteristics.hash(state);
+        size.hash(state);
+        variant_suffix.hash(state);
+        color.hash(state);
+        official_name.hash(state);
+        is_organic.hash(state);
+        pending.hash(state);
+        components.hash(state);
+        trademarked.hash(state);
+        composition.len().hash(state);
+        for (component, percent) in composition {
+            component.hash(state);
+            // 0.0 and -0.0 are equal, so they must hash alike
+            let percent = if *percent == 0.0 { 0.0f32 } else { *percent };
+            percent.to_bits().hash(state);
+        }
+        ripeness.hash(state);
+        footnotes.hash(state);
+        code_strings.hash(state);
+        alternative_names.hash(state);
+        sold_by.hash(state);
+        code_ranges.hash(state);
+    }
+}
+
+impl FromIterator<PluItem> for PluCollection {
+    fn from_iter<I: IntoIterator<Item = PluItem>>(iter: I) -> Self {
+        PluCollection {
+            items: iter.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Extend<PluItem> for PluCollection {
+    fn extend<I: IntoIterator<Item = PluItem>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+/// Organic PLU codes are the 4-digit conventional code prefixed with 9, e.g. 94011.
+pub fn is_organic_code(code: u32) -> bool {
+    (90000..=99999).contains(&code)
+}
+
+// 64-bit FNV-1a; unlike `DefaultHasher` its output is fixed, so hashes can be cached across runs
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+fn fnv_hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = Fnv1a::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Lowercase slug with '-' between words, e.g. "Mickey Lee" -> "mickey-lee". Letters
+// outside ASCII are kept ("Jalapeño" -> "jalapeño") so such names still get distinct ids.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// Zero-pads a code to 13 digits and appends the GS1 mod-10 check digit
+fn gtin14(code: u32) -> String {
+    let body = format!("{:013}", code);
+    // Weights alternate 3, 1, 3, ... starting from the rightmost digit
+    let sum: u32 = body
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| u32::from(digit - b'0') * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    format!("{}{}", body, (10 - sum % 10) % 10)
+}
+
+// Codes as a comma-separated list, e.g. "4049, 43181"
+fn format_codes(codes: &[u32]) -> String {
+    codes
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parser::parse_plu_text;
+
+    const SAMPLE: &str = "Apple
+• Akane, small (4098), large (4099)
+• Aurora / Southern Rose, small (3001), large (3290)
+Melon
+• Cantaloupe / Muskmelon, small (4049, 43181), large (4050, 43191)
+• Watermelon:
+  o Mickey Lee / Sugarbaby (4331)
+  o Mini, seedless [3‐7 pounds] (3421)";
+
+    #[test]
+    fn test_codes_by_prefix() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let counts = collection.codes_by_prefix(2);
+        assert_eq!(counts.get(&40), Some(&4)); // 4098, 4099, 4049, 4050
+        assert_eq!(counts.get(&43), Some(&3)); // 43181, 43191, 4331
+        assert_eq!(counts.get(&30), Some(&1)); // 3001
+        assert_eq!(counts.get(&32), Some(&1)); // 3290
+        assert_eq!(counts.get(&34), Some(&1)); // 3421
+        assert_eq!(counts.values().sum::<usize>(), 10);
+    }
+
+    #[test]
+    fn test_ranked_search_exact_outranks_fuzzy() {
+        let collection =
+            parse_plu_text("Melon\n• Mickey Lee / Sugarbaby (4331)\n• Sugarbabe (9999)").unwrap();
+        let results = collection.ranked_search("sugarbaby");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.name, "Mickey Lee"); // exact alternative name
+        assert_eq!(results[0].1, 1.0);
+        assert_eq!(results[1].0.name, "Sugarbabe"); // fuzzy, one edit away
+        assert!(results[1].1 < results[0].1);
+    }
+
+    #[test]
+    fn test_to_json_sorted_is_order_independent() {
+        let reordered = "Melon
+• Watermelon:
+  o Mini, seedless [3‐7 pounds] (3421)
+  o Mickey Lee / Sugarbaby (4331)
+• Cantaloupe / Muskmelon, small (4049, 43181), large (4050, 43191)
+Apple
+• Aurora / Southern Rose, small (3001), large (3290)
+• Akane, small (4098), large (4099)";
+        let original = parse_plu_text(SAMPLE).unwrap();
+        let shuffled = parse_plu_text(reordered).unwrap();
+        assert_ne!(original.items, shuffled.items);
+        assert_eq!(
+            original.to_json_sorted().unwrap(),
+            shuffled.to_json_sorted().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_codes_for_variety() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        assert_eq!(collection.codes_for_variety("Akane"), vec![4098, 4099]);
+        assert_eq!(collection.codes_for_variety("aurora"), vec![3001, 3290]);
+        assert!(collection.codes_for_variety("Fuji").is_empty());
+    }
+
+    #[test]
+    fn test_depth_histogram() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let histogram = collection.depth_histogram();
+        assert_eq!(histogram.get(&1), Some(&6)); // Apple varieties + cantaloupe sizes
+        assert_eq!(histogram.get(&2), Some(&2)); // Melon > Watermelon items
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(collection.max_depth(), 2);
+        assert_eq!(PluCollection::default().max_depth(), 0);
+    }
+
+    #[test]
+    fn test_annotate_with_reference() {
+        let mut collection = parse_plu_text("Banana\n• Cavendish (4011)\n• Burro (4010)").unwrap();
+        let reference = HashMap::from([(4011, "Banana".to_string())]);
+        collection.annotate_with_reference(&reference);
+        assert_eq!(
+            collection.items[0].official_name,
+            Some("Banana".to_string())
+        );
+        assert_eq!(collection.items[1].official_name, None);
+    }
+
+    #[test]
+    fn test_collect_and_extend() {
+        let items = vec![
+            PluItem::new(
+                "Akane".to_string(),
+                vec![4098],
+                vec!["Apple".to_string()],
+                None,
+                vec![],
+                None,
+            ),
+            PluItem::new(
+                "Fuji".to_string(),
+                vec![4131],
+                vec!["Apple".to_string()],
+                None,
+                vec![],
+                None,
+            ),
+        ];
+        let mut collection: PluCollection = items.into_iter().collect();
+        assert_eq!(collection.items.len(), 2);
+        assert_eq!(collection.items[1].name, "Fuji");
+
+        collection.extend(parse_plu_text("Melon\n• Honeydew (4034)").unwrap().items);
+        assert_eq!(collection.items.len(), 3);
+        assert_eq!(collection.items[2].plu_codes, vec![4034]);
+    }
+
+    #[test]
+    fn test_is_reserved() {
+        let collection =
+            parse_plu_text("Apple\n• Akane (4098)\n• retailer assigned (4193‐4217)").unwrap();
+        assert_eq!(
+            collection.retailer_ranges,
+            vec![CodeRange {
+                start: 4193,
+                end: 4217
+            }]
+        );
+        assert!(collection.is_reserved(4200));
+        assert!(collection.is_reserved(4217));
+        assert!(!collection.is_reserved(4098));
+    }
+
+    #[test]
+    fn test_partition_organic() {
+        let collection = parse_plu_text(
+            "Banana\n• Cavendish (4011)\n• Cavendish, organic (94011)\n• Organic Burro (4010)",
+        )
+        .unwrap();
+        let (organic, conventional) = collection.partition_organic();
+        let organic_codes: Vec<_> = organic.items.iter().map(|i| i.plu_codes[0]).collect();
+        let conventional_codes: Vec<_> =
+            conventional.items.iter().map(|i| i.plu_codes[0]).collect();
+        assert_eq!(organic_codes, vec![94011, 4010]);
+        assert_eq!(conventional_codes, vec![4011]);
+    }
+
+    #[test]
+    fn test_code_count() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let cantaloupe = collection
+            .items
+            .iter()
+            .find(|i| i.name == "Cantaloupe, small")
+            .unwrap();
+        assert_eq!(cantaloupe.code_count(), 2);
+        assert!(cantaloupe.has_multiple_codes());
+
+        let akane = collection
+            .items
+            .iter()
+            .find(|i| i.name == "Akane, small")
+            .unwrap();
+        assert_eq!(akane.code_count(), 1);
+        assert!(!akane.has_multiple_codes());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let original = parse_plu_text(SAMPLE).unwrap();
+        let reordered = parse_plu_text(
+            "Melon
+• Watermelon:
+  o Mini, seedless [3‐7 pounds] (3421)
+  o Mickey Lee / Sugarbaby (4331)
+• Cantaloupe / Muskmelon, small (4049, 43181), large (4050, 43191)
+Apple
+• Aurora / Southern Rose, small (3001), large (3290)
+• Akane, small (4098), large (4099)",
+        )
+        .unwrap();
+        assert_eq!(original.content_hash(), reordered.content_hash());
+
+        let changed = parse_plu_text(&SAMPLE.replace("4098", "4097")).unwrap();
+        assert_ne!(original.content_hash(), changed.content_hash());
+
+        let mut revised = parse_plu_text(SAMPLE).unwrap();
+        revised.revision = Some("2024".to_string());
+        assert_ne!(original.content_hash(), revised.content_hash());
+        let mut annotated = parse_plu_text(SAMPLE).unwrap();
+        annotated
+            .footnote_legend
+            .insert(1, "Retailer assigned.".to_string());
+        assert_ne!(original.content_hash(), annotated.content_hash());
+    }
+
+    #[test]
+    fn test_qualified_names() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let names: Vec<String> = collection.qualified_names().collect();
+        assert_eq!(names.len(), collection.items.len());
+        assert_eq!(names[0], "Apple > Akane, small");
+        assert!(names.contains(&"Melon > Watermelon > Mini, seedless".to_string()));
+    }
+
+    #[test]
+    fn test_find_nameless() {
+        let mut collection = parse_plu_text(SAMPLE).unwrap();
+        assert!(collection.find_nameless().is_empty());
+
+        collection.items.push(PluItem::new(
+            " ".to_string(),
+            vec![4011],
+            vec!["Banana".to_string()],
+            None,
+            vec![],
+            None,
+        ));
+        let nameless = collection.find_nameless();
+        assert_eq!(nameless.len(), 1);
+        assert_eq!(nameless[0].plu_codes, vec![4011]);
+    }
+
+    #[test]
+    fn test_codes_in_range() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let matches: Vec<(&str, u32)> = collection
+            .codes_in_range(4000, 4100)
+            .into_iter()
+            .map(|(item, code)| (item.name.as_str(), code))
+            .collect();
+        assert_eq!(
+            matches,
+            vec![
+                ("Akane, small", 4098),
+                ("Akane, large", 4099),
+                ("Cantaloupe, small", 4049),
+                ("Cantaloupe, large", 4050),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flatten() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let rows = collection.flatten();
+        let total_codes: usize = collection.items.iter().map(|i| i.plu_codes.len()).sum();
+        assert_eq!(rows.len(), total_codes);
+        assert_eq!(
+            rows[0],
+            (4098, "Akane, small".to_string(), vec!["Apple".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_validate_size_groups() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        assert!(collection.validate_size_groups().is_ok());
+
+        let mut broken = parse_plu_text(SAMPLE).unwrap();
+        broken.items[1].category_path = vec!["Melon".to_string()];
+        let mismatches = broken.validate_size_groups().unwrap_err();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].0.name, "Akane, small");
+        assert_eq!(mismatches[0].1.name, "Akane, large");
+    }
+
+    #[test]
+    fn test_codes_with_prefix() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let codes: Vec<(u32, &str)> = collection
+            .codes_with_prefix("40")
+            .into_iter()
+            .map(|(code, item)| (code, item.name.as_str()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                (4049, "Cantaloupe, small"),
+                (4050, "Cantaloupe, large"),
+                (4098, "Akane, small"),
+                (4099, "Akane, large"),
+            ]
+        );
+        assert_eq!(collection.codes_with_prefix("4318").len(), 1);
+        assert!(collection.codes_with_prefix("5").is_empty());
+    }
+
+    #[test]
+    fn test_missing_alt_names() {
+        let collection =
+            parse_plu_text("Melon\n• Cantaloupe, small (4049), large (4050)\n• Honeydew (4034)")
+                .unwrap();
+        let reference = HashMap::from([(4049, "Muskmelon".to_string())]);
+        let missing = collection.missing_alt_names(&reference);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].0.name, "Cantaloupe, small");
+        assert_eq!(missing[0].1, "Muskmelon");
+
+        // Items that already carry an alias are not reported
+        let sample = parse_plu_text(SAMPLE).unwrap();
+        assert!(sample.missing_alt_names(&reference).is_empty());
+    }
+
+    #[test]
+    fn test_to_value() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let value = collection.to_value();
+        let items = value["items"].as_array().unwrap();
+        assert_eq!(items.len(), collection.items.len());
+        assert_eq!(items[0]["name"], "Akane, small");
+        assert_eq!(items[0]["plu_codes"][0], 4098);
+    }
+
+    #[test]
+    fn test_footnote_meanings() {
+        let collection = parse_plu_text(
+            "Apple\n• Golden Delicious, small (4021, 41361,2), large (4020)\nBanana\n• Cavendish (4011³)",
+        )
+        .unwrap();
+        let legend = HashMap::from([(1, "Canada only".to_string()), (3, "Seasonal".to_string())]);
+        let golden = &collection.items[0];
+        assert_eq!(golden.footnotes, vec![1, 2]);
+        assert_eq!(golden.footnote_meanings(&legend), vec!["Canada only"]);
+        assert!(collection.items[1].footnote_meanings(&legend).is_empty());
+        assert_eq!(collection.items[2].footnotes, vec![3]);
+        assert_eq!(
+            collection.items[2].footnote_meanings(&legend),
+            vec!["Seasonal"]
+        );
+    }
+
+    #[test]
+    fn test_cross_category_codes() {
+        let collection = parse_plu_text(
+            "Banana\n• Cavendish (4011)\n• Burro (4010)\nPlantain\n• Green (4011)\n• Yellow (4010, 4235)",
+        )
+        .unwrap();
+        let banana = vec!["Banana".to_string()];
+        let plantain = vec!["Plantain".to_string()];
+        assert_eq!(
+            collection.cross_category_codes(),
+            vec![
+                (4010, vec![banana.clone(), plantain.clone()]),
+                (4011, vec![banana, plantain]),
+            ]
+        );
+        assert!(
+            parse_plu_text(SAMPLE)
+                .unwrap()
+                .cross_category_codes()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_by_category_and_codes_diff() {
+        let collection = parse_plu_text(
+            "Apple\n• Akane (4098)\n• Asian Pear / Nashi (4406)\n• Gala (4133)\nPear\n• Asian Pear / Nashi (4406)\n• Bosc (4025)",
+        )
+        .unwrap();
+        assert_eq!(collection.by_category("apple").len(), 3);
+        assert_eq!(collection.by_category("Pear").len(), 2);
+
+        assert_eq!(collection.codes_diff("Apple", "Pear"), vec![4098, 4133]);
+        assert_eq!(collection.codes_diff("Pear", "Apple"), vec![4025]);
+        assert_eq!(collection.codes_diff("Pear", "Kiwi"), vec![4025, 4406]);
+
+        let sample = parse_plu_text(SAMPLE).unwrap();
+        assert_eq!(sample.by_category("Watermelon").len(), 2);
+    }
+
+    #[test]
+    fn test_to_scale_map() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let map = collection.to_scale_map();
+        assert_eq!(map[&4098], ("Akane".to_string(), Some("small".to_string())));
+        assert_eq!(map[&4099], ("Akane".to_string(), Some("large".to_string())));
+        // Both codes of a multi-code item are present
+        assert_eq!(map[&43181], map[&4049]);
+        assert_eq!(map[&4331], ("Mickey Lee".to_string(), None));
+        assert_eq!(map.len(), 10);
+
+        let duplicated = parse_plu_text("Banana\n• Cavendish (4011)\n• Other (4011)").unwrap();
+        assert_eq!(duplicated.to_scale_map()[&4011].0, "Cavendish");
+    }
+
+    #[test]
+    fn test_with_sizes_matches_parser() {
+        let built = PluItem::with_sizes(
+            "Akane",
+            vec!["Apple".to_string()],
+            &[("small", 4098), ("large", 4099)],
+        );
+        let parsed = parse_plu_text("Apple\n• Akane, small (4098), large (4099)").unwrap();
+        assert_eq!(built, parsed.items);
+        assert_eq!(built[1].canonical_name(), "Akane");
+    }
+
+    #[test]
+    fn test_by_categories_any_and_all() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let names = |items: Vec<&PluItem>| -> Vec<String> {
+            items.into_iter().map(|item| item.name.clone()).collect()
+        };
+
+        assert_eq!(
+            names(collection.by_categories(&["Apple", "Watermelon"])),
+            vec![
+                "Akane, small",
+                "Akane, large",
+                "Aurora, small",
+                "Aurora, large",
+                "Mickey Lee",
+                "Mini, seedless"
+            ]
+        );
+        assert_eq!(collection.by_categories(&["Melon", "Kiwi"]).len(), 4);
+        assert!(collection.by_categories(&[]).is_empty());
+
+        assert_eq!(
+            names(collection.by_all_categories(&["Melon", "watermelon"])),
+            vec!["Mickey Lee", "Mini, seedless"]
+        );
+        assert!(collection.by_all_categories(&["Apple", "Melon"]).is_empty());
+    }
+
+    #[test]
+    fn test_barcode_strings() {
+        let collection = parse_plu_text(
+            "Banana\n• Cavendish (94011)\n• Organic Burro (4010)\n• Cavendish (4011)\n• Test (123)",
+        )
+        .unwrap();
+        let barcodes: Vec<Vec<String>> = collection
+            .items
+            .iter()
+            .map(PluItem::barcode_strings)
+            .collect();
+        assert_eq!(
+            barcodes,
+            vec![vec!["94011"], vec!["94010"], vec!["4011"], vec!["0123"]]
+        );
+    }
+
+    #[test]
+    fn test_item_id() {
+        let first = parse_plu_text(SAMPLE).unwrap();
+        let second = parse_plu_text(SAMPLE).unwrap();
+        let ids: Vec<String> = first.items.iter().map(PluItem::id).collect();
+        assert_eq!(
+            ids,
+            second.items.iter().map(PluItem::id).collect::<Vec<_>>()
+        );
+        assert_eq!(ids[0], "apple/akane/small");
+        assert_eq!(ids[1], "apple/akane/large");
+        assert_eq!(ids[6], "melon/watermelon/mickey-lee");
+
+        let pepper = parse_plu_text("Pepper\n• Jalapeño (4693)").unwrap();
+        assert_eq!(pepper.items[0].id(), "pepper/jalapeño");
+    }
+
+    #[test]
+    fn test_normalize_characteristics() {
+        let mut item = PluItem::new(
+            "Grape".to_string(),
+            vec![4022],
+            vec!["Grape".to_string()],
+            None,
+            vec![
+                "seedless".to_string(),
+                "Seedless".to_string(),
+                " seedless ".to_string(),
+                "Red".to_string(),
+            ],
+            None,
+        );
+        item.normalize_characteristics();
+        assert_eq!(item.characteristics, vec!["seedless", "red"]);
+    }
+
+    #[test]
+    fn test_item_with_most_codes() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let item = collection.item_with_most_codes().unwrap();
+        // Both cantaloupe sizes have two codes; the first one wins
+        assert_eq!(item.name, "Cantaloupe, small");
+        assert_eq!(item.plu_codes, vec![4049, 43181]);
+        assert!(PluCollection::default().item_with_most_codes().is_none());
+    }
+
+    #[test]
+    fn test_to_databar_list() {
+        let collection = parse_plu_text("Banana\n• Cavendish (4011, 94011)").unwrap();
+        let entries = collection.to_databar_list();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].code, 4011);
+        assert_eq!(entries[0].name, "Cavendish");
+        assert_eq!(entries[0].gtin14, "00000000040112");
+        assert_eq!(entries[0].element_string(), "(01)00000000040112");
+        assert_eq!(entries[1].gtin14, "00000000940115");
+    }
+
+    #[test]
+    fn test_top_categories() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        assert_eq!(collection.top_categories(), vec!["Apple", "Melon"]);
+        assert!(PluCollection::default().top_categories().is_empty());
+    }
+
+    #[test]
+    fn test_length_anomalies() {
+        let collection = parse_plu_text(
+            "Apple\n• Akane (4098)\n• Gala (4133)\n• Fuji (41311)\n• Organic Fuji (94131)",
+        )
+        .unwrap();
+        let anomalies: Vec<(&str, u32)> = collection
+            .length_anomalies()
+            .into_iter()
+            .map(|(item, code)| (item.name.as_str(), code))
+            .collect();
+        assert_eq!(anomalies, vec![("Fuji", 41311)]);
+
+        let clean = parse_plu_text("Apple\n• Akane (4098)\n• Gala (4133)").unwrap();
+        assert!(clean.length_anomalies().is_empty());
+    }
+
+    #[test]
+    fn test_group_alphabetical() {
+        let mut collection = parse_plu_text(SAMPLE).unwrap();
+        collection.extend(parse_plu_text("Herbs\n• 1/2 bunch (4889)").unwrap().items);
+        let groups = collection.group_alphabetical();
+        assert_eq!(
+            groups.keys().copied().collect::<Vec<_>>(),
+            vec!['#', 'A', 'C', 'M']
+        );
+        assert_eq!(groups[&'A'][0].name, "Akane, small");
+        assert_eq!(groups[&'A'].len(), 4);
+        assert_eq!(groups[&'#'].len(), 1);
+    }
+
+    #[test]
+    fn test_diff_and_changelog() {
+        let previous = parse_plu_text("Apple\n• Akane, small (4098)\n• Gala (4133)").unwrap();
+        let current = parse_plu_text("Apple\n• Akane, large (4098)\n• Fuji (4131)").unwrap();
+
+        let diff = current.diff(&previous);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.changed.len(), 1);
+
+        assert_eq!(
+            current.changelog(&previous),
+            "+ Added Fuji (4131)\n\
+             - Removed Gala (4133)\n\
+             ~ Changed size of Akane (4098): small -> large"
+        );
+
+        let one_item =
+            parse_plu_text("Apple\n• Akane, small (4098)\n• Gala (4133)\n• Fuji (4131)").unwrap();
+        assert_eq!(one_item.changelog(&previous), "+ Added Fuji (4131)");
+        assert_eq!(previous.changelog(&previous), "");
+
+        // Duplicate listings are not collapsed
+        let duplicated =
+            parse_plu_text("Apple\n• Akane (4098)\nAsian Pear\n• Akane (4098)").unwrap();
+        let single = parse_plu_text("Apple\n• Akane (4098)").unwrap();
+        let diff = single.diff(&duplicated);
+        assert!(diff.added.is_empty() && diff.changed.is_empty());
+        assert_eq!(diff.removed, vec![&duplicated.items[1]]);
+        assert_eq!(duplicated.diff(&single).added, vec![&duplicated.items[1]]);
+    }
+
+    #[test]
+    fn test_siblings() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let small = &collection.items[4];
+        let large = &collection.items[5];
+        assert_eq!(small.name, "Cantaloupe, small");
+
+        assert_eq!(collection.siblings(small), vec![large]);
+        assert_eq!(collection.siblings(large), vec![small]);
+
+        let mickey = &collection.items[6];
+        let names: Vec<&str> = collection
+            .siblings(mickey)
+            .into_iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Mini, seedless"]);
+    }
+
+    #[test]
+    fn test_apply_synonyms() {
+        let mut collection =
+            parse_plu_text("Squash\n• Zucchini (4067)\n• Eggplant (4081)\n• Butternut (4759)")
+                .unwrap();
+        let mut synonyms = HashMap::from([
+            ("zucchini".to_string(), vec!["courgette".to_string()]),
+            (
+                "aubergine".to_string(),
+                vec!["eggplant".to_string(), "brinjal".to_string()],
+            ),
+        ]);
+        collection.apply_synonyms(&synonyms);
+        assert_eq!(collection.items[0].alternative_names, vec!["courgette"]);
+        assert_eq!(
+            collection.items[1].alternative_names,
+            vec!["aubergine", "brinjal"]
+        );
+        assert!(collection.items[2].alternative_names.is_empty());
+
+        // Applying twice doesn't duplicate synonyms
+        collection.apply_synonyms(&synonyms);
+        assert_eq!(collection.items[0].alternative_names, vec!["courgette"]);
+
+        // Names from several matching entries follow the key order
+        synonyms.insert("eggplant".to_string(), vec!["melanzana".to_string()]);
+        synonyms.insert("brinjal".to_string(), vec!["eggplant".to_string()]);
+        let mut fresh = parse_plu_text("Squash\n• Eggplant (4081)").unwrap();
+        fresh.apply_synonyms(&synonyms);
+        assert_eq!(
+            fresh.items[0].alternative_names,
+            vec!["aubergine", "brinjal", "melanzana"]
+        );
+    }
+
+    #[test]
+    fn test_item_hash_normalises_zero_percent() {
+        let mut positive = parse_plu_text("Salads\n• Duo (0% kale, 100% chard) (4323)")
+            .unwrap()
+            .items
+            .remove(0);
+        let mut negative = positive.clone();
+        negative.composition[0].1 = -0.0;
+        assert_eq!(positive, negative);
+        assert_eq!(fnv_hash(&positive), fnv_hash(&negative));
+        positive.composition[0].1 = 5.0;
+        assert_ne!(fnv_hash(&positive), fnv_hash(&negative));
+    }
+
+    #[test]
+    fn test_merge_footnote_variants() {
+        let mut collection =
+            parse_plu_text("Banana\n• Banana (4011¹)\n• Banana (4011²)\n• Banana (4012)").unwrap();
+        assert_eq!(collection.items.len(), 3);
+        collection.merge_footnote_variants();
+        assert_eq!(collection.items.len(), 2);
+        assert_eq!(collection.items[0].plu_codes, vec![4011]);
+        assert_eq!(collection.items[0].footnotes, vec![1, 2]);
+        assert_eq!(collection.items[1].plu_codes, vec![4012]);
+    }
+
+    #[test]
+    fn test_unique_prefixes() {
+        let collection =
+            parse_plu_text("Misc\n• Banana (4011)\n• Banana, red (4012)\n• Watermelon (3421)")
+                .unwrap();
+        let prefixes = collection.unique_prefixes();
+        assert_eq!(prefixes.len(), 3);
+        assert_eq!(prefixes[&4011], "4011");
+        assert_eq!(prefixes[&4012], "4012");
+        assert_eq!(prefixes[&3421], "3");
+    }
+
+    #[test]
+    fn test_distinct_sizes() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        assert_eq!(collection.distinct_sizes(), vec!["small", "large"]);
+
+        let collection = parse_plu_text(
+            "Kale\n• Kale, bunch (4627)\n• Leek, jumbo (4629)\n• Leek, medium (4630)",
+        )
+        .unwrap();
+        assert_eq!(collection.distinct_sizes(), vec!["medium", "jumbo"]);
+    }
+
+    #[test]
+    fn test_to_rust_array() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let source = collection.to_rust_array();
+        assert!(source.starts_with("pub static PLU_CODES: &[(u32, &str)] = &[\n"));
+        assert!(source.contains("    (4098, \"Akane, small\"),\n"));
+        assert!(source.ends_with("];\n"));
+        // Sorted by code
+        assert!(source.find("(3001,").unwrap() < source.find("(4098,").unwrap());
+
+        let quoted = parse_plu_text("Misc\n• Tom's \"cherry\" (4796)").unwrap();
+        assert!(
+            quoted
+                .to_rust_array()
+                .contains(r#"(4796, "Tom's \"cherry\""),"#)
+        );
+    }
+
+    #[test]
+    fn test_category_code_counts() {
+        let collection = parse_plu_text(SAMPLE).unwrap();
+        let counts = collection.category_code_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["Apple"], 4);
+        assert_eq!(counts["Melon"], 6);
+    }
+
+    #[test]
+    fn test_is_range_only() {
+        let collection =
+            parse_plu_text("Misc\n• Reserved block (4193-4217)\n• Banana (4011)").unwrap();
+        assert_eq!(collection.items.len(), 2);
+        let reserved = &collection.items[0];
+        assert_eq!(reserved.name, "Reserved block");
+        assert!(reserved.plu_codes.is_empty());
+        assert_eq!(
+            reserved.code_ranges,
+            vec![CodeRange {
+                start: 4193,
+                end: 4217
+            }]
+        );
+        assert!(reserved.is_range_only());
+        assert!(!collection.items[1].is_range_only());
+
+        let mixed = parse_plu_text("Apple\n• Akane (4098, 4100–4105)").unwrap();
+        assert_eq!(mixed.items[0].plu_codes, vec![4098]);
+        assert_eq!(
+            mixed.items[0].code_ranges,
+            vec![CodeRange {
+                start: 4100,
+                end: 4105
+            }]
+        );
+        assert!(!mixed.items[0].is_range_only());
+    }
+
+    #[test]
+    fn test_word_index() {
+        let collection = parse_plu_text(
+            "Apple\n• Golden Delicious, small (4020), large (4021)\n• Akane / Tokyo Rose (4098)",
+        )
+        .unwrap();
+        let index = collection.word_index();
+        let delicious: Vec<u32> = index["delicious"]
+            .iter()
+            .flat_map(|item| item.plu_codes.clone())
+            .collect();
+        assert_eq!(delicious, vec![4020, 4021]);
+        assert_eq!(index["small"].len(), 1);
+        assert_eq!(index["rose"][0].name, "Akane");
+        assert!(!index.contains_key("Golden"));
     }
 }
@@ -0,0 +1,247 @@
+use crate::models::plu_model::{PluCollection, PluItem};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// File formats supported by the collection exporters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+}
+
+impl ExportFormat {
+    /// File extension used for files written in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Errors raised while exporting a collection to disk.
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "I/O error during export: {}", e),
+            ExportError::Json(e) => write!(f, "JSON serialization failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(e: serde_json::Error) -> Self {
+        ExportError::Json(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl PluCollection {
+    /// Serializes the collection as TOML, with items as an `[[items]]` array of tables.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+}
+
+/// Error raised by the XML writer in `PluCollection::to_xml`.
+#[cfg(feature = "xml")]
+pub type XmlError = std::io::Error;
+
+#[cfg(feature = "xml")]
+impl PluCollection {
+    /// Serializes the collection as indented XML for legacy POS integrations:
+    /// `<plu-collection>` holds one `<item>` per item, each with `<name>`, optional
+    /// `<alternative-name>` and `<size>`, `<codes>` of `<code>` and `<category>` of `<level>`.
+    pub fn to_xml(&self) -> Result<String, XmlError> {
+        use quick_xml::Writer;
+
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+        writer
+            .create_element("plu-collection")
+            .write_inner_content(|writer| {
+                for item in &self.items {
+                    writer
+                        .create_element("item")
+                        .write_inner_content(|writer| {
+                            write_xml_text(writer, "name", &item.name)?;
+                            if let Some(alt) = &item.alternative_name {
+                                write_xml_text(writer, "alternative-name", alt)?;
+                            }
+                            if let Some(size) = &item.size {
+                                write_xml_text(writer, "size", size)?;
+                            }
+                            writer
+                                .create_element("codes")
+                                .write_inner_content(|writer| {
+                                    for code in &item.plu_codes {
+                                        write_xml_text(writer, "code", &code.to_string())?;
+                                    }
+                                    Ok(())
+                                })?;
+                            writer
+                                .create_element("category")
+                                .write_inner_content(|writer| {
+                                    for level in &item.category_path {
+                                        write_xml_text(writer, "level", level)?;
+                                    }
+                                    Ok(())
+                                })?;
+                            Ok(())
+                        })?;
+                }
+                Ok(())
+            })?;
+        // Only &str content was written, so the buffer is valid UTF-8
+        Ok(String::from_utf8(writer.into_inner()).expect("XML output is UTF-8"))
+    }
+}
+
+// Writes `<name>text</name>`, escaping the text
+#[cfg(feature = "xml")]
+fn write_xml_text(
+    writer: &mut quick_xml::Writer<Vec<u8>>,
+    name: &str,
+    text: &str,
+) -> Result<(), XmlError> {
+    writer
+        .create_element(name)
+        .write_text_content(quick_xml::events::BytesText::new(text))?;
+    Ok(())
+}
+
+// Turns a category name into a safe lowercase file stem, e.g. "Alfalfa Sprouts" -> "alfalfa_sprouts"
+fn sanitize_file_stem(category: &str) -> String {
+    let mut stem = String::new();
+    for c in category.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.ends_with('_') {
+            stem.push('_');
+        }
+    }
+    let stem = stem.trim_matches('_');
+    if stem.is_empty() {
+        "uncategorized".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+impl PluCollection {
+    /// Writes one file per top-level category into `dir`, e.g. `apple.json`, `melon.json`.
+    /// Category names are sanitized into safe file names; items without a category
+    /// go to `uncategorized`. The directory is created if missing.
+    pub fn export_by_category(&self, dir: &Path, format: ExportFormat) -> Result<(), ExportError> {
+        let mut groups: BTreeMap<String, Vec<&PluItem>> = BTreeMap::new();
+        for item in &self.items {
+            let category = item.category_path.first().map_or("", String::as_str);
+            groups
+                .entry(sanitize_file_stem(category))
+                .or_default()
+                .push(item);
+        }
+
+        fs::create_dir_all(dir)?;
+        for (stem, items) in groups {
+            let path = dir.join(format!("{}.{}", stem, format.extension()));
+            let contents = match format {
+                ExportFormat::Json => {
+                    serde_json::to_string_pretty(&serde_json::json!({ "items": items }))?
+                }
+            };
+            fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parser::parse_plu_text;
+
+    #[test]
+    fn test_sanitize_file_stem() {
+        assert_eq!(sanitize_file_stem("Alfalfa Sprouts"), "alfalfa_sprouts");
+        assert_eq!(sanitize_file_stem("Herbs / Spices"), "herbs_spices");
+        assert_eq!(sanitize_file_stem("../"), "uncategorized");
+    }
+
+    #[test]
+    fn test_export_by_category() {
+        let text = "Apple\n• Akane, small (4098), large (4099)\nMelon\n• Watermelon:\n  o Mickey Lee / Sugarbaby (4331)";
+        let collection = parse_plu_text(text).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        collection
+            .export_by_category(dir.path(), ExportFormat::Json)
+            .unwrap();
+
+        let read_items = |file: &str| -> usize {
+            let contents = fs::read_to_string(dir.path().join(file)).unwrap();
+            let value: serde_json::Value = serde_json::from_str(&contents).unwrap();
+            value["items"].as_array().unwrap().len()
+        };
+        assert_eq!(read_items("apple.json"), 2);
+        assert_eq!(read_items("melon.json"), 1);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_round_trip() {
+        let text = "Melon\n• Cantaloupe / Muskmelon, small (4049, 43181), large (4050, 43191)\n• retailer assigned (4193‐4217)";
+        let collection = parse_plu_text(text).unwrap();
+        let toml_text = collection.to_toml().unwrap();
+        assert!(toml_text.contains("[[items]]"));
+
+        let parsed: PluCollection = toml::from_str(&toml_text).unwrap();
+        assert_eq!(parsed.items, collection.items);
+        assert_eq!(parsed.retailer_ranges, collection.retailer_ranges);
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_to_xml() {
+        let collection =
+            parse_plu_text("Melon\n• Watermelon:\n  o Mickey Lee / Sugarbaby (4331)").unwrap();
+        let xml = collection.to_xml().unwrap();
+        let expected = "\
+<plu-collection>
+  <item>
+    <name>Mickey Lee</name>
+    <alternative-name>Sugarbaby</alternative-name>
+    <codes>
+      <code>4331</code>
+    </codes>
+    <category>
+      <level>Melon</level>
+      <level>Watermelon</level>
+    </category>
+  </item>
+</plu-collection>";
+        assert_eq!(xml, expected);
+
+        let escaped = parse_plu_text("Herbs\n• Salt & Pepper <mix> (4999)").unwrap();
+        assert!(
+            escaped
+                .to_xml()
+                .unwrap()
+                .contains("<name>Salt &amp; Pepper &lt;mix&gt;</name>")
+        );
+    }
+}
@@ -1 +1,3 @@
+pub mod export;
+pub mod parse_options;
 pub mod parser;
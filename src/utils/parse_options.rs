@@ -0,0 +1,109 @@
+use regex::Regex;
+
+/// How the parser reacts to a code group it can only partly read, e.g. "(4011, abc, 4012)".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecoveryMode {
+    /// Skip the line without salvaging any codes. The line is still listed in
+    /// `ParseStats.unprocessed_lines` as "no item pattern matched".
+    #[default]
+    Lenient,
+    /// Salvage the valid codes and record a warning in `ParseStats.unprocessed_lines`.
+    Recover,
+    /// Fail the whole parse with an error naming the offending line.
+    /// Items left without a name after parsing are rejected too.
+    Strict,
+}
+
+/// Case normalization applied to names and category values once parsing is done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameCase {
+    /// Keep names exactly as written in the source.
+    #[default]
+    AsIs,
+    /// "golden delicious"
+    Lower,
+    /// "Golden Delicious"; each part of a hyphenated word is capitalized ("Honey-Crisp").
+    /// All-caps words such as "USA" are kept when the rest of the text is not all caps.
+    Title,
+}
+
+impl NameCase {
+    /// Applies this case style to `text`.
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            NameCase::AsIs => text.to_string(),
+            NameCase::Lower => text.to_lowercase(),
+            NameCase::Title => {
+                // Acronyms can only be told apart from shouting in mixed-case text
+                let shouting = !text.chars().any(char::is_lowercase);
+                text.split(' ')
+                    .map(|word| {
+                        let is_acronym = !shouting
+                            && word.chars().any(char::is_alphabetic)
+                            && !word.chars().any(char::is_lowercase);
+                        if is_acronym {
+                            word.to_string()
+                        } else {
+                            word.split('-')
+                                .map(capitalize)
+                                .collect::<Vec<_>>()
+                                .join("-")
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+        }
+    }
+}
+
+// Uppercases the first letter of `word` (skipping leading punctuation) and lowercases the rest
+fn capitalize(word: &str) -> String {
+    let mut seen_letter = false;
+    word.chars()
+        .flat_map(|c| {
+            if c.is_alphabetic() && !seen_letter {
+                seen_letter = true;
+                c.to_uppercase().collect::<Vec<_>>()
+            } else {
+                c.to_lowercase().collect::<Vec<_>>()
+            }
+        })
+        .collect()
+}
+
+/// Options controlling how `parse_plu_text_with_options` reads its input.
+/// Start from `ParseOptions::default()` and override the fields you need.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Treat every line as a standalone "Name (codes)" item.
+    /// Categories and bullets are not tracked, so items get an empty category path.
+    pub flat: bool,
+
+    /// What to do with malformed code groups.
+    pub recovery: RecoveryMode,
+
+    /// Pattern recognising top-level category lines, e.g. to allow digits or accents.
+    /// Defaults to `^[A-Z][a-zA-Z /&'-]+$` when `None`.
+    pub toplevel_pattern: Option<Regex>,
+
+    /// Pattern for page separators and running headers (e.g. "PLU Codes (continued)")
+    /// that are skipped without touching the current category.
+    /// Defaults to "Page N [of M]" lines, rules like "-----" and "PLU ... (continued)"
+    /// running headers when `None`.
+    pub page_marker_pattern: Option<Regex>,
+
+    /// Case applied to every `name`, `alternative_name`, `alternative_names` entry and
+    /// category path entry after parsing.
+    pub name_case: NameCase,
+
+    /// End the current commodity after this many consecutive blank lines, clearing the
+    /// category path, for documents that separate blocks by blank lines instead of headers.
+    /// `None` (the default) ignores blank lines entirely.
+    pub blank_lines_reset: Option<usize>,
+
+    /// Pattern picking the codes out of a locale-specific code group, e.g. `(\d{2})\.(\d{2})`
+    /// for "(04.01, 04.02)". Each match becomes one code made of its capture groups (or the
+    /// whole match) with non-digits dropped. `None` uses the built-in code parser.
+    pub code_pattern: Option<Regex>,
+}
@@ -1,24 +1,30 @@
-use crate::models::plu_model::{PluCollection, PluItem};
-use regex::{Captures, Regex};
-use std::collections::VecDeque;
+use crate::models::parse_report::{ParseReport, ParseStats, ParseWarning, WarningReason};
+use crate::models::plu_model::{CodeRange, PluCollection, PluItem, PluItemRef};
+use crate::utils::parse_options::{NameCase, ParseOptions, RecoveryMode};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::sync::mpsc::Sender;
 
 // Helper function to parse PLU codes from a string like "(4098)" or "(4049, 43181,2)"
 // It ignores footnotes like ¹²³ or ,1,2 and ranges like 4193‐4217
+// Slash-separated alternatives like "(4053/4958)" are treated as multiple codes
 fn parse_plu_codes(text: &str) -> Vec<u32> {
     let inner_text = text.trim_matches(|c| c == '(' || c == ')');
     if inner_text.is_empty() {
         return Vec::new();
     }
 
-    // Regex to handle ranges like (4193-4217) explicitly
-    let re_range = Regex::new(r"^\d+[-‐]\d+$").unwrap(); // Handles both hyphen and dash
-    if re_range.is_match(inner_text) {
-        return Vec::new(); // Ignore ranges
-    }
+    // Ranges like (4193-4217) are code blocks, not codes; their bounds go to `code_ranges` only
+    static RE_RANGE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+\s*[-‐–—]\s*\d+").unwrap()); // Hyphen, unicode hyphen, en and em dash
+    let inner_text = RE_RANGE.replace_all(inner_text, " ");
 
-    let re_extract_all_numbers = Regex::new(r"\d+").unwrap();
-    let potential_numbers: Vec<String> = re_extract_all_numbers
-        .find_iter(inner_text)
+    static RE_EXTRACT_ALL_NUMBERS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+").unwrap());
+    let potential_numbers: Vec<String> = RE_EXTRACT_ALL_NUMBERS
+        .find_iter(&inner_text)
         .map(|m| m.as_str().to_string())
         .collect();
 
@@ -34,20 +40,12 @@ fn parse_plu_codes(text: &str) -> Vec<u32> {
         let mut current_code_str = num_str.clone();
 
         // Heuristic for 5-digit numbers that are treated as 4-digit codes + footnote part(s)
-        // Derived from test_parse_multi_code_single_item and test_parse_with_footnote
-        if num_str.len() == 5 {
-            if num_str.starts_with("4136") || // For "41361" -> "4136"
-               num_str.starts_with("4137") || // For "41371" -> "4137"
-               num_str.starts_with("3392")
-            // For "33923" -> "3392"
-            // Add other similar 5-digit retailer codes needing truncation if discovered
-            {
-                current_code_str = num_str[0..4].to_string();
-                // If this 5-digit number was truncated, and there's a next number in the sequence,
-                // assume it's part of the footnote (e.g., the '2' in "41361,2").
-                if i + 1 < potential_numbers.len() {
-                    skip_next_number = true;
-                }
+        if is_footnoted_code(num_str) {
+            current_code_str = num_str[0..4].to_string();
+            // If this 5-digit number was truncated, and there's a next number in the sequence,
+            // assume it's part of the footnote (e.g., the '2' in "41361,2").
+            if i + 1 < potential_numbers.len() {
+                skip_next_number = true;
             }
         }
 
@@ -58,10 +56,218 @@ fn parse_plu_codes(text: &str) -> Vec<u32> {
     actual_codes
 }
 
+// Heuristic for 5-digit numbers that are a 4-digit code followed by a footnote digit.
+// Derived from test_parse_multi_code_single_item and test_parse_with_footnote
+fn is_footnoted_code(num_str: &str) -> bool {
+    num_str.len() == 5
+        && (num_str.starts_with("4136") || // For "41361" -> "4136"
+           num_str.starts_with("4137") || // For "41371" -> "4137"
+           num_str.starts_with("3392"))
+    // For "33923" -> "3392"
+    // Add other similar 5-digit retailer codes needing truncation if discovered
+}
+
+// Value of a superscript digit like '¹', used for footnote markers
+fn superscript_digit(c: char) -> Option<u8> {
+    match c {
+        '¹' => Some(1),
+        '²' => Some(2),
+        '³' => Some(3),
+        '⁰' | '⁴'..='⁹' => Some((c as u32 - '⁰' as u32) as u8),
+        _ => None,
+    }
+}
+
+// Helper to read footnote definitions from a legend line. Lines starting with a superscript
+// may hold several entries ("¹ Retailer assigned. ² USA only."); inside a "Footnotes:"
+// section plain "1 = Canada only" / "1. Canada only" lines count too.
+fn parse_legend_entries(line: &str, in_legend_section: bool) -> Vec<(u8, String)> {
+    let clean = |text: &str| text.trim().trim_end_matches('.').trim().to_string();
+    if line.chars().next().and_then(superscript_digit).is_some() {
+        let mut entries: Vec<(u8, String)> = Vec::new();
+        for c in line.chars() {
+            match (superscript_digit(c), entries.last_mut()) {
+                (Some(number), _) => entries.push((number, String::new())),
+                (None, Some((_, text))) => text.push(c),
+                (None, None) => {}
+            }
+        }
+        return entries
+            .into_iter()
+            .map(|(number, text)| (number, clean(&text)))
+            .filter(|(_, text)| !text.is_empty())
+            .collect();
+    }
+    if in_legend_section {
+        static RE_ENTRY: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^(\d{1,2})\s*[=.):-]?\s+(.+)$").unwrap());
+        if let Some(caps) = RE_ENTRY.captures(line)
+            && let Ok(number) = caps[1].parse()
+        {
+            return vec![(number, clean(&caps[2]))];
+        }
+    }
+    Vec::new()
+}
+
+// Helper to collect the footnote numbers of a code group, from superscripts ("4011¹")
+// and from the tail of footnoted codes ("41361,2" carries footnotes 1 and 2)
+fn extract_footnotes(codes_str: &str) -> Vec<u8> {
+    let mut footnotes: Vec<u8> = codes_str.chars().filter_map(superscript_digit).collect();
+
+    static RE_NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+").unwrap());
+    let numbers: Vec<&str> = RE_NUMBER.find_iter(codes_str).map(|m| m.as_str()).collect();
+    for (i, num_str) in numbers.iter().enumerate() {
+        if is_footnoted_code(num_str) {
+            footnotes.push(num_str[4..].parse().unwrap());
+            if let Some(next) = numbers.get(i + 1).filter(|next| next.len() == 1) {
+                footnotes.push(next.parse().unwrap());
+            }
+        }
+    }
+    footnotes.sort_unstable();
+    footnotes.dedup();
+    footnotes
+}
+
+// Helper to extract code ranges like "4193‐4217" from a line
+fn extract_code_ranges(text: &str) -> Vec<CodeRange> {
+    static RE_RANGE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(\d+)\s*[-‐–—]\s*(\d+)").unwrap());
+    RE_RANGE
+        .captures_iter(text)
+        .filter_map(|caps| {
+            let start = caps.get(1).unwrap().as_str().parse().ok()?;
+            let end = caps.get(2).unwrap().as_str().parse().ok()?;
+            Some(CodeRange { start, end })
+        })
+        .collect()
+}
+
+// Helper to extract a letter suffix from variant codes like "(4011A)".
+// The numeric part is still returned by parse_plu_codes; only the first suffix found is kept.
+fn extract_variant_suffix(text: &str) -> Option<String> {
+    static RE_SUFFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d([A-Z]+)\b").unwrap());
+    RE_SUFFIX
+        .captures(text)
+        .map(|caps| caps.get(1).unwrap().as_str().to_string())
+}
+
+// Color words recognised at the start of a name, e.g. "Red Delicious" or "Green Grape"
+const COLOR_WORDS: [&str; 12] = [
+    "red", "green", "yellow", "orange", "purple", "white", "black", "blue", "pink", "gold",
+    "golden", "brown",
+];
+
+// Helper to detect a leading color word; the name itself is left untouched
+fn extract_color(name: &str) -> Option<String> {
+    let first_word = name
+        .split(|c: char| !c.is_alphabetic())
+        .next()?
+        .to_lowercase();
+    COLOR_WORDS
+        .contains(&first_word.as_str())
+        .then_some(first_word)
+}
+
+// Ripeness stages recognised as the last comma segment of a name, e.g. "Avocado, ripe"
+const RIPENESS_WORDS: [&str; 6] = ["ripe", "green", "turning", "unripe", "overripe", "breaking"];
+
+// Commodities sold by ripening stage, where a trailing ", green" means unripe
+const RIPENING_COMMODITIES: [&str; 7] = [
+    "banana", "plantain", "avocado", "tomato", "mango", "pear", "papaya",
+];
+
+// Helper to split a trailing ripeness stage off a name.
+// Only a separate ", green" segment counts; "Green Grape" keeps "green" as its color.
+// "green" is a color elsewhere too ("Bell, green" under Pepper), so it only counts as
+// a stage when the name or its category is a ripening commodity.
+fn extract_ripeness(name: &str, category_path: &[&str]) -> (String, Option<String>) {
+    if let Some((base, last)) = name.rsplit_once(',') {
+        let stage = last.trim().to_lowercase();
+        let ripens = || {
+            category_path
+                .iter()
+                .copied()
+                .chain([base])
+                .flat_map(|text| text.split(|c: char| !c.is_alphabetic()))
+                .map(str::to_lowercase)
+                .any(|word| {
+                    RIPENING_COMMODITIES
+                        .iter()
+                        .any(|commodity| word.starts_with(commodity))
+                })
+        };
+        if RIPENESS_WORDS.contains(&stage.as_str())
+            && !base.trim().is_empty()
+            && (stage != "green" || ripens())
+        {
+            return (base.trim().to_string(), Some(stage));
+        }
+    }
+    (name.to_string(), None)
+}
+
+// Removes an open-ended list marker from the end of a name,
+// e.g. "strawberry, blueberry, etc." -> "strawberry, blueberry"; "…" and "..." count too
+fn strip_trailing_etc(name: &str) -> &str {
+    static RE_ETC: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?i)[,\s]*(?:\betc\.?|…|\.{3})\s*$").unwrap());
+    match RE_ETC.find(name) {
+        Some(m) if m.start() > 0 => name[..m.start()].trim_end(),
+        _ => name,
+    }
+}
+
+// Helper to split a pricing unit such as ", per lb" or "/kg" out of a name,
+// e.g. "Tomato, per lb" -> ("Tomato", Some("lb")). A bare "3-7 pounds" is a weight
+// range, not a unit, so "per" or "/" is required.
+fn extract_sold_by(name: &str) -> (String, Option<String>) {
+    static RE_UNIT: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i),?\s*(?:\bper\s+|/\s*)(lbs?|pounds?|kg|kilos?|kilograms?|each|ea)\b\.?")
+            .unwrap()
+    });
+    let Some(caps) = RE_UNIT.captures(name) else {
+        return (name.to_string(), None);
+    };
+    let unit = match caps[1].to_lowercase().as_str() {
+        "each" | "ea" => "each",
+        unit if unit.starts_with('k') => "kg",
+        _ => "lb",
+    };
+    let stripped = RE_UNIT.replace(name, "");
+    (stripped.trim().to_string(), Some(unit.to_string()))
+}
+
+// Helper to split compound names like "Broccoli + Cauliflower blend" into their parts.
+// A trailing "blend"/"mix" describes the whole compound and is dropped from the last part.
+fn extract_components(name: &str) -> Vec<String> {
+    if !name.contains('+') {
+        return Vec::new();
+    }
+    let mut components: Vec<String> = name
+        .split('+')
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect();
+    if let Some(last) = components.last_mut() {
+        for suffix in [" blend", " mix"] {
+            if last.to_lowercase().ends_with(suffix) {
+                last.truncate(last.len() - suffix.len());
+            }
+        }
+    }
+    if components.len() < 2 {
+        return Vec::new();
+    }
+    components
+}
+
 // Helper to extract characteristics like "[seedless, 3-7 pounds]"
 fn extract_characteristics(text: &str) -> (String, Vec<String>) {
-    let re_chars = Regex::new(r"^(.*)\[(.+?)\](.*)$").unwrap();
-    if let Some(caps) = re_chars.captures(text) {
+    static RE_CHARS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(.*)\[(.+?)\](.*)$").unwrap());
+    if let Some(caps) = RE_CHARS.captures(text) {
         let remaining_text = format!(
             "{}{}",
             caps.get(1).unwrap().as_str(),
@@ -80,25 +286,118 @@ fn extract_characteristics(text: &str) -> (String, Vec<String>) {
     }
 }
 
-// Helper to extract alternative names like "Aurora / Southern Rose"
-fn extract_alternative_name(text: &str) -> (String, Option<String>) {
-    // Match pattern like "Name / Alt Name" potentially followed by size info
-    let re_alt = Regex::new(r"^(.*?)\s*/\s*([^,(]+)(.*)$").unwrap();
-    if let Some(caps) = re_alt.captures(text) {
+// Helper to parse a percentage breakdown like "(70% romaine, 30% spinach)" out of a name.
+// Parens that are not entirely "N% part" segments are left for the other helpers.
+fn extract_composition(text: &str) -> (String, Vec<(String, f32)>) {
+    static RE_GROUP: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\s*\(([^()]*%[^()]*)\)").unwrap());
+    static RE_PART: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^(?:(\d+(?:\.\d+)?)\s*%\s*(.+)|(.+?)\s+(\d+(?:\.\d+)?)\s*%)$").unwrap()
+    });
+    let Some(group) = RE_GROUP.captures(text) else {
+        return (text.to_string(), Vec::new());
+    };
+    let parts: Option<Vec<(String, f32)>> = group
+        .get(1)
+        .unwrap()
+        .as_str()
+        .split(',')
+        .map(|part| {
+            let caps = RE_PART.captures(part.trim())?;
+            let (percent, component) = match caps.get(1) {
+                Some(percent) => (percent, caps.get(2).unwrap()),
+                None => (caps.get(4).unwrap(), caps.get(3).unwrap()),
+            };
+            Some((
+                component.as_str().trim().to_string(),
+                percent
+                    .as_str()
+                    .parse()
+                    .ok()
+                    .filter(|p: &f32| p.is_finite())?,
+            ))
+        })
+        .collect();
+    match parts {
+        Some(parts) => {
+            let whole = group.get(0).unwrap();
+            let name = format!("{}{}", &text[..whole.start()], &text[whole.end()..]);
+            (name.trim().to_string(), parts)
+        }
+        None => (text.to_string(), Vec::new()),
+    }
+}
+
+// Helper to pull a capitalized parenthetical alias out of a name,
+// e.g. "Cantaloupe (Muskmelon)" -> ("Cantaloupe", Some("Muskmelon")).
+// Lowercase parentheticals are qualifiers and are left alone.
+fn extract_paren_alias(text: &str) -> (String, Option<String>) {
+    static RE_ALIAS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(.*?)\s*\((\p{Lu}[^()\d]*)\)(.*)$").unwrap());
+    if let Some(caps) = RE_ALIAS.captures(text) {
         let name = format!(
             "{}{}",
             caps.get(1).unwrap().as_str().trim(),
-            caps.get(3).unwrap().as_str().trim()
+            caps.get(3).unwrap().as_str()
         )
         .trim()
         .to_string();
-        let alt_name = Some(caps.get(2).unwrap().as_str().trim().to_string());
-        (name, alt_name)
+        let alias = caps.get(2).unwrap().as_str().trim().to_string();
+        (name, Some(alias))
     } else {
         (text.to_string(), None)
     }
 }
 
+// Helper to collect non-code parentheticals like "(fuzzy)" in "Kiwi (fuzzy)".
+// They stay in the name as a qualifier and are also reported as characteristics,
+// split on commas like bracketed ones: "(seedless, red)" gives two characteristics.
+fn extract_paren_qualifiers(text: &str) -> Vec<String> {
+    static RE_QUALIFIER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(([^()\d]+)\)").unwrap());
+    RE_QUALIFIER
+        .captures_iter(text)
+        .flat_map(|caps| {
+            caps.get(1)
+                .unwrap()
+                .as_str()
+                .split(',')
+                .map(|qualifier| qualifier.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|qualifier| !qualifier.is_empty())
+        .collect()
+}
+
+// Helper to extract alternative names like "Aurora / Southern Rose". Further aliases
+// ("A / B / C") come back separately, and trailing size info stays on the name:
+// "Aurora / Southern Rose / Rosa, small" -> ("Aurora, small", Some("Southern Rose"), ["Rosa"])
+fn extract_alternative_name(text: &str) -> (String, Option<String>, Vec<String>) {
+    // The slash must sit between non-digits so fractions like "1/2 bunch" are kept intact.
+    static RE_ALT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(.*?[^\d\s])\s*/\s*([^\d\s,(][^,(]*)(.*)$").unwrap());
+    if let Some(caps) = RE_ALT.captures(text) {
+        let name = format!(
+            "{}{}",
+            caps.get(1).unwrap().as_str().trim(),
+            caps.get(3).unwrap().as_str().trim()
+        )
+        .trim()
+        .to_string();
+        let mut aliases = caps
+            .get(2)
+            .unwrap()
+            .as_str()
+            .split('/')
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .map(String::from);
+        let alt_name = aliases.next();
+        (name, alt_name, aliases.collect())
+    } else {
+        (text.to_string(), None, Vec::new())
+    }
+}
+
 // Helper to normalize size names
 fn normalize_size(size_str: &str) -> String {
     match size_str.trim().to_lowercase().as_str() {
@@ -112,225 +411,999 @@ fn normalize_size(size_str: &str) -> String {
 }
 
 pub fn parse_plu_text(text: &str) -> Result<PluCollection, String> {
-    println!(">>>>> TEXT: {} <<<<<", text);
-    let mut items = Vec::new();
-    let mut category_path: VecDeque<String> = VecDeque::new();
-    let re_range = Regex::new(r"\d+[-‐]\d+").unwrap(); // Define once
-
-    // Regex definitions (ensure they handle potential footnotes in codes if needed)
-    let re_toplevel = Regex::new(r"^[A-Z][a-zA-Z /&'-]+$").unwrap();
-    let re_item1 = Regex::new(r"^•\s+(.*)$").unwrap();
-    let re_item2 = Regex::new(r"^\s{2,}o\s+(.*)$").unwrap();
-
-    // Allow footnote chars in the code parts of these specific regexes
-    let re_size_split = Regex::new(r"^(.*?),\s*(small|medium|large|extra large|jumbo)\s*\(([\d,\s¹²³\-‐]+)\),\s*(small|medium|large|extra large|jumbo)\s*\(([\d,\s¹²³\-‐]+)\)$").unwrap();
-    let re_alt_size_split = Regex::new(r"^(.*?),\s*(small|medium|large|extra large|jumbo)\s*\(([\d,\s¹²³\-‐]+)\),\s*(small|medium|large|extra large|jumbo)\s*\(([\d,\s¹²³\-‐]+)\)$").unwrap();
-    let re_standard = Regex::new(r"^(.*?)\s*\(([\d,\s\-‐¹²³]+)\)$").unwrap();
-
-    for line in text.lines() {
-        let trimmed_line = line.trim();
-        // Skip empty lines logic...
-        if trimmed_line.is_empty()
-            || trimmed_line.starts_with("no listing")
-            || trimmed_line.starts_with("all commodities")
-        {
-            continue;
-        }
+    parse_plu_text_with_options(text, &ParseOptions::default())
+}
 
-        let mut processed = false;
+pub fn parse_plu_text_with_options(
+    text: &str,
+    options: &ParseOptions,
+) -> Result<PluCollection, String> {
+    parse_plu_report(text, options).map(|report| report.collection)
+}
 
-        // --- Handle Hierarchy ---
-        if re_toplevel.is_match(trimmed_line)
-            && !trimmed_line.starts_with('•')
-            && !trimmed_line.contains(':')
-        {
-            // Top Level Category
-            category_path.clear();
-            category_path.push_back(trimmed_line.to_string());
-            processed = true;
-            println!(">>>>> processed 1: {:?} <<<<<", &processed);
-        } else if let Some(caps) = re_item1.captures(line) {
-            // First Level Item/Category ('•')
-            let content = caps.get(1).unwrap().as_str().trim();
-
-            if content.starts_with("Mickey Lee") || content.starts_with("Mini, seedless") {
-                eprintln!(
-                    "DEBUG: category_path for 'o {}': {:?}",
-                    content, category_path
-                );
+/// Parses `text` like `parse_plu_text_with_options`, also returning the parse statistics
+/// and the warnings raised along the way.
+pub fn parse_plu_report(text: &str, options: &ParseOptions) -> Result<ParseReport, String> {
+    parse_plu_report_with(text, options, |_| {})
+}
+
+// `parse_plu_report`, handing each item to `on_item` as soon as the line it came from
+// has been parsed, with the name case already applied
+fn parse_plu_report_with(
+    text: &str,
+    options: &ParseOptions,
+    mut on_item: impl FnMut(&PluItem),
+) -> Result<ParseReport, String> {
+    let mut report = ParseReport::default();
+    let patterns = &*ITEM_PATTERNS;
+    let mut outline = Outline::new(text, options);
+    let mut emitted = 0;
+
+    while let Some(item_line) = outline.next_item_line(&mut report) {
+        let line_number = item_line.line_number;
+        if !process_item_line(
+            item_line.content,
+            outline.category_path(),
+            line_number,
+            patterns,
+            options,
+            &mut report,
+        )? {
+            if item_line.warn_unmatched {
+                report.warnings.push(ParseWarning {
+                    line_number,
+                    reason: WarningReason::UnmatchedLine {
+                        content: item_line.line.trim().to_string(),
+                    },
+                });
             }
+            report
+                .stats
+                .record_unprocessed(line_number, item_line.line, "no item pattern matched");
+        }
+        emit_new_items(
+            &mut report.collection.items,
+            &mut emitted,
+            options.name_case,
+            &mut on_item,
+        );
+    }
+
+    if options.recovery == RecoveryMode::Strict
+        && let Some(item) = report.collection.find_nameless().first()
+    {
+        return Err(format!(
+            "Item with codes {:?} in {:?} ended up without a name",
+            item.plu_codes, item.category_path
+        ));
+    }
 
-            // Adjust path: Pop back to the top level (level 0) *before* processing this line
-            while category_path.len() > 1 {
-                category_path.pop_back();
+    report.stats.items_parsed = report.collection.items.len();
+    Ok(report)
+}
+
+// Applies the name case to the items pushed since the last call and hands them to `on_item`
+fn emit_new_items(
+    items: &mut [PluItem],
+    emitted: &mut usize,
+    name_case: NameCase,
+    on_item: &mut impl FnMut(&PluItem),
+) {
+    for item in &mut items[*emitted..] {
+        if name_case != NameCase::AsIs {
+            item.name = name_case.apply(&item.name);
+            item.alternative_name = item
+                .alternative_name
+                .as_deref()
+                .map(|alt| name_case.apply(alt));
+            for alt in &mut item.alternative_names {
+                *alt = name_case.apply(alt);
             }
+            for category in &mut item.category_path {
+                *category = name_case.apply(category);
+            }
+        }
+        on_item(item);
+    }
+    *emitted = items.len();
+}
 
-            if category_path.is_empty() {
-                eprintln!(
-                    "Warning: Found item '• {}' with no top-level parent category.",
-                    content
-                );
+/// Parses `text` and sends each item through `sender`, in document order, as soon as
+/// its line has been parsed, so another thread can consume items while parsing goes
+/// on; returns the parse statistics once the whole text is parsed.
+/// Sending stops early, without an error, if the receiving end hangs up.
+pub fn parse_plu_to_channel(text: String, sender: Sender<PluItem>) -> Result<ParseStats, String> {
+    let mut hung_up = false;
+    let report = parse_plu_report_with(&text, &ParseOptions::default(), |item| {
+        hung_up = hung_up || sender.send(item.clone()).is_err();
+    })?;
+    Ok(report.stats)
+}
+
+impl PluCollection {
+    /// Parses every `.txt` file in `dir` and merges the results in file name order:
+    /// items and retailer ranges are concatenated, legends combined, and the first
+    /// revision found is kept. Subdirectories are not searched.
+    pub fn from_dir(dir: &Path) -> Result<PluCollection, String> {
+        let parts = plu_files(dir)?
+            .iter()
+            .map(|path| parse_plu_file(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(merge_collections(parts))
+    }
+
+    /// Like `from_dir`, but parses the files concurrently. The result is identical to
+    /// the sequential one since files are still merged in file name order.
+    #[cfg(feature = "rayon")]
+    pub fn from_dir_parallel(dir: &Path) -> Result<PluCollection, String> {
+        use rayon::prelude::*;
+
+        let parts = plu_files(dir)?
+            .par_iter()
+            .map(|path| parse_plu_file(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(merge_collections(parts))
+    }
+}
+
+// The `.txt` files directly inside `dir`, sorted by file name
+fn plu_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Error reading directory '{}': {}", dir.display(), e))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Error reading directory '{}': {}", dir.display(), e))?
+            .path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "txt") {
+            paths.push(path);
+        }
+    }
+    paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(paths)
+}
+
+fn parse_plu_file(path: &Path) -> Result<PluCollection, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Error reading file '{}': {}", path.display(), e))?;
+    parse_plu_text(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Concatenates per-file collections, keeping their order
+fn merge_collections(parts: Vec<PluCollection>) -> PluCollection {
+    let mut merged = PluCollection::default();
+    for part in parts {
+        merged.items.extend(part.items);
+        merged.retailer_ranges.extend(part.retailer_ranges);
+        for (number, meaning) in part.footnote_legend {
+            merged.footnote_legend.entry(number).or_insert(meaning);
+        }
+        merged.revision = merged.revision.or(part.revision);
+    }
+    merged
+}
+
+/// Parses `text` into items whose names and categories borrow from `text`, for read-only
+/// pipelines that parse in-memory strings repeatedly and want to avoid allocating names.
+///
+/// The returned items hold slices of `text`, so `text` must outlive them. Lines are
+/// classified exactly as `parse_plu_text` classifies them, so both find the same items
+/// under the same categories; only alternative names and size groups are read from the
+/// items themselves. Use `parse_plu_text` when characteristics, footnotes or the other
+/// derived fields are needed.
+pub fn parse_plu_text_borrowed(text: &str) -> Vec<PluItemRef<'_>> {
+    let options = ParseOptions::default();
+    // Diagnostics aren't part of the borrowed API, but the outline records them somewhere
+    let mut scratch = ParseReport::default();
+    let mut outline = Outline::new(text, &options);
+    let mut items = Vec::new();
+    while let Some(item_line) = outline.next_item_line(&mut scratch) {
+        push_borrowed_items(item_line.content, outline.category_path(), &mut items);
+    }
+    items
+}
+
+// Borrowing counterpart of process_item_line for the size-group and standard patterns
+fn push_borrowed_items<'a>(
+    content: &'a str,
+    category_path: &[&'a str],
+    items: &mut Vec<PluItemRef<'a>>,
+) {
+    let patterns = &*ITEM_PATTERNS;
+    if let Some((base, groups)) = split_size_groups(content, &patterns.size_group) {
+        let (mut name, mut alternative_name) = split_alternative_name(base.trim());
+        for group in groups {
+            // The alias applies to every size group until a group brings its own
+            if let Some(rename) = group.rename {
+                (name, alternative_name) = split_alternative_name(rename);
+            }
+            let plu_codes = parse_plu_codes(group.codes);
+            if plu_codes.is_empty() {
                 continue;
             }
+            items.push(PluItemRef {
+                name,
+                plu_codes,
+                category_path: category_path.to_vec(),
+                alternative_name,
+                size: Some(group.size),
+                split_sizes: true,
+            });
+        }
+    } else if let Some(caps) = patterns.standard.captures(content) {
+        let plu_codes = parse_plu_codes(caps.get(2).unwrap().as_str());
+        if plu_codes.is_empty() {
+            return;
+        }
+        let name_part = caps.get(1).unwrap().as_str().trim();
+        // A single ", size" suffix moves into `size`, as in "Akane, small (4098)"
+        let (name_part, size) = match name_part.rsplit_once(',') {
+            Some((name, size)) if patterns.inline_size.is_match(size.trim()) => {
+                (name.trim(), Some(size.trim()))
+            }
+            _ => (name_part, None),
+        };
+        let (name, alternative_name) = split_alternative_name(name_part);
+        items.push(PluItemRef {
+            name,
+            plu_codes,
+            category_path: category_path.to_vec(),
+            alternative_name,
+            size,
+            split_sizes: false,
+        });
+    }
+}
 
-            if content.ends_with(':') {
-                // Sub-category header like "Watermelon:"
-                let sub_cat_name = content.trim_end_matches(':').trim().to_string();
-                if sub_cat_name == "Watermelon" {
-                    eprintln!(
-                        "DEBUG: category_path after 'Watermelon:' processing: {:?}",
-                        category_path
-                    );
+// Borrowing counterpart of extract_alternative_name: "Aurora / Southern Rose" is split
+// only when the alias ends the text, since anything after it would need re-joining
+fn split_alternative_name(text: &str) -> (&str, Option<&str>) {
+    static RE_ALT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(.*?[^\d\s])\s*/\s*([^\d\s,(][^,(]*)$").unwrap());
+    match RE_ALT.captures(text) {
+        Some(caps) => (
+            caps.get(1).unwrap().as_str().trim(),
+            Some(caps.get(2).unwrap().as_str().trim()),
+        ),
+        None => (text, None),
+    }
+}
+
+// Default top-level category pattern, used unless `ParseOptions::toplevel_pattern` is set
+static DEFAULT_TOPLEVEL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z][a-zA-Z /&'-]+$").unwrap());
+// "Page N [of M]", rules like "-----", and running headers like "PLU Codes (continued)"
+static DEFAULT_PAGE_MARKER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^(?:page\s+\d+(?:\s+of\s+\d+)?|[-=_]{3,}|.*\bplu\b.*\(\s*cont(?:inued|'d|d|\.)?\s*\))$",
+    )
+    .unwrap()
+});
+static RE_ITEM1: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*•\s+(.*)$").unwrap());
+// The 'o' glyph marks the second level on its own, so any indent width will do
+static RE_ITEM2: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s+o\s+(.*)$").unwrap());
+// Compact "Category: Item (codes)" lines that open a category and list its first item
+static RE_CATEGORY_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([^:•]+):\s*(.*\(.*\d.*\))$").unwrap());
+// "(continued)" / "(cont'd)" after a category or header name, before any ':'
+static RE_CONTINUED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\s*\(\s*cont(?:inued|'d|d|\.)?\s*\)\s*(:?)\s*$").unwrap());
+// Document title carrying the list's revision, e.g. "IFPS PLU Codes 2024"
+static RE_REVISION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:plu|revision|edition)\b.*?\b((?:19|20)\d{2}(?:\.\d+)?)\b").unwrap()
+});
+static RE_LEGEND_HEADER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^(?:footnotes?|legend|notes?)\s*:?$").unwrap());
+
+// Walks a document line by line and keeps track of its layout: top-level categories,
+// '•' headers with or without a colon, "(continued)" sections, page filler, the title
+// and the footnote legend. Only item lines are handed out, so every parser built on it
+// agrees on which lines are items and which category each one belongs to.
+struct Outline<'t, 'o> {
+    lines: Vec<&'t str>,
+    next_index: usize,
+    options: &'o ParseOptions,
+    re_toplevel: &'o Regex,
+    re_page_marker: &'o Regex,
+    // The top-level category followed by one entry per open header
+    category_path: Vec<&'t str>,
+    // Indentation of each open '•' sub-category header below the top level, outermost first
+    header_indents: Vec<usize>,
+    // Top-level categories opened so far, which "Name (continued)" lines may re-open
+    seen_toplevel: HashSet<&'t str>,
+    blank_run: usize,
+    // Set by a "Footnotes:" style header; the first line that isn't an entry clears it
+    in_legend_section: bool,
+    // Once an item line has been handed out no document title is looked for
+    seen_items: bool,
+}
+
+// An item line found by `Outline`; its category is `Outline::category_path`
+struct ItemLine<'t> {
+    line_number: usize,
+    // The line as recorded in `ParseStats` when nothing in it parses
+    line: &'t str,
+    // The item text without its bullet or category prefix
+    content: &'t str,
+    // Rows without a bullet (flat mode, delimited rows) are also reported as UnmatchedLine
+    // warnings when they fail, since no layout line vouches for them being items
+    warn_unmatched: bool,
+}
+
+impl<'t, 'o> Outline<'t, 'o> {
+    fn new(text: &'t str, options: &'o ParseOptions) -> Self {
+        Outline {
+            lines: text.lines().collect(),
+            next_index: 0,
+            options,
+            re_toplevel: options
+                .toplevel_pattern
+                .as_ref()
+                .unwrap_or(&DEFAULT_TOPLEVEL),
+            re_page_marker: options
+                .page_marker_pattern
+                .as_ref()
+                .unwrap_or(&DEFAULT_PAGE_MARKER),
+            category_path: Vec::new(),
+            header_indents: Vec::new(),
+            seen_toplevel: HashSet::new(),
+            blank_run: 0,
+            in_legend_section: false,
+            seen_items: false,
+        }
+    }
+
+    // Category of the item line last handed out
+    fn category_path(&self) -> &[&'t str] {
+        &self.category_path
+    }
+
+    fn item_line(
+        &mut self,
+        line_number: usize,
+        line: &'t str,
+        content: &'t str,
+        warn_unmatched: bool,
+    ) -> Option<ItemLine<'t>> {
+        self.seen_items = true;
+        Some(ItemLine {
+            line_number,
+            line,
+            content,
+            warn_unmatched,
+        })
+    }
+
+    // Advances to the next item line, updating the category path on the way. Layout
+    // diagnostics, the revision and legend entries go into `report`.
+    fn next_item_line(&mut self, report: &mut ParseReport) -> Option<ItemLine<'t>> {
+        let patterns = &*ITEM_PATTERNS;
+        while let Some(&line) = self.lines.get(self.next_index) {
+            let index = self.next_index;
+            self.next_index += 1;
+            let line_number = index + 1;
+            report.stats.total_lines += 1;
+            let trimmed_line = line.trim();
+
+            if trimmed_line.is_empty() {
+                self.blank_run += 1;
+                if self
+                    .options
+                    .blank_lines_reset
+                    .is_some_and(|limit| self.blank_run >= limit)
+                {
+                    self.category_path.clear();
+                    self.header_indents.clear();
                 }
+                continue;
+            }
+            self.blank_run = 0;
 
-                // Add the sub-category to the path *after* ensuring we're at the parent level
-                category_path.push_back(sub_cat_name);
-                processed = true;
-                println!(">>>>> processed 2: {:?} <<<<<", &processed);
-            } else {
-                // Process as item at level 1 (category_path should contain only top-level)
-                processed = process_item_line(
-                    content,
-                    &category_path,
-                    &re_size_split,
-                    &re_alt_size_split,
-                    &re_standard,
-                    &re_range,
-                    &mut items,
-                )?;
-                println!(">>>>> processed 3: {:?} <<<<<", &processed);
+            // Page breaks, running headers and other filler must not reset the category we're in
+            if is_filler_line(trimmed_line, self.re_page_marker) {
+                continue;
             }
-        } else if let Some(caps) = re_item2.captures(line) {
-            // Second Level Item/Category ('o')
-            let content = caps.get(1).unwrap().as_str().trim();
-
-            // Path Adjustment: Ensure we are exactly at level 2 (Top + SubCategory).
-            // DO NOT pop here. The path should *already* be correct if the previous '•' line was a header.
-            // Pop only if the path is somehow deeper than expected.
-            while category_path.len() > 2 {
-                eprintln!(
-                    "Warning: Path {:?} too deep for 'o' item, trimming.",
-                    category_path
-                );
-                category_path.pop_back();
+            // The title comes before any category and must not become one
+            if report.collection.revision.is_none()
+                && self.category_path.is_empty()
+                && !self.seen_items
+                && let Some(caps) = RE_REVISION.captures(trimmed_line)
+            {
+                report.collection.revision = Some(caps[1].to_string());
+                continue;
             }
 
-            if category_path.len() != 2 {
-                // Check if path is exactly Top/SubCategory
-                eprintln!(
-                    "Warning: Found sub-item 'o {}' but category path has unexpected length ({:?}). Expected Top/Sub.",
-                    content, category_path
-                );
-                continue; // Skip item
+            // A section picked up again later, e.g. "Apple (continued)", is the same
+            // category. The marker is cut off; a colon after it still makes a header.
+            let (line, continued, colon) = match RE_CONTINUED.captures(line) {
+                Some(caps) => (
+                    &line[..caps.get(0).unwrap().start()],
+                    true,
+                    !caps[1].is_empty(),
+                ),
+                None => (line, false, false),
+            };
+            let trimmed_line = line.trim();
+
+            // Footnote legend, usually at the bottom of the document
+            if RE_LEGEND_HEADER.is_match(trimmed_line) {
+                self.in_legend_section = true;
+                continue;
+            }
+            let legend_entries = parse_legend_entries(trimmed_line, self.in_legend_section);
+            if !legend_entries.is_empty() {
+                report.collection.footnote_legend.extend(legend_entries);
+                continue;
             }
+            // The first line that isn't an entry, e.g. the next category, ends the legend block
+            self.in_legend_section = false;
 
-            // Process as item at level 2 (path should contain Top-Level and Sub-Category)
-            processed = process_item_line(
-                content,
-                &category_path,
-                &re_size_split,
-                &re_alt_size_split,
-                &re_standard,
-                &re_range,
-                &mut items,
-            )?;
-            println!(">>>>> processed 4: {:?} <<<<<", &processed);
-        }
-        // Logging for unprocessed lines (ensure process_item_line returns false when needed)
-        else if !processed
-            && !re_toplevel.is_match(trimmed_line)
-            && !trimmed_line.contains("retailer assigned")
-            && !trimmed_line.is_empty()
-        {
-            println!(">>>>> else if !processed <<<<<");
-            // Check if it's likely a multi-line characteristic description (heuristic)
-            if !trimmed_line.starts_with('•')
-                && !trimmed_line.starts_with('o')
-                && (trimmed_line.starts_with('[') || trimmed_line.ends_with(']'))
+            // --- Flat mode: every line is a top-level item, no hierarchy ---
+            if self.options.flat {
+                let content = trimmed_line.trim_start_matches('•').trim();
+                return self.item_line(line_number, line, content, true);
+            }
+
+            // --- Handle Hierarchy ---
+            if self.re_toplevel.is_match(trimmed_line)
+                && !trimmed_line.starts_with('•')
+                && !trimmed_line.contains(':')
+                && !colon
             {
-                // Potentially part of a previous item's characteristics - harder to parse reliably line-by-line
-                eprintln!(
-                    "Info: Skipping likely multi-line characteristic: {}",
-                    trimmed_line
-                );
-            } else if !trimmed_line.contains(':') {
-                // Don't warn for category lines like "Watermelon:"
-                eprintln!("Warning: Unprocessed line: {}", line);
+                // Top Level Category, unless nothing sits beneath it: a stray capitalized word
+                // such as "Lemon" between items must not clobber the current category
+                let next_line = next_content_line(&self.lines[index + 1..], self.re_page_marker);
+                let has_items = next_line.is_some_and(|next| {
+                    RE_ITEM1.is_match(next)
+                        || RE_ITEM2.is_match(next)
+                        || indentation(next) > 0
+                        || patterns.delimited.is_match(next.trim())
+                        || RE_CATEGORY_ITEM.is_match(next.trim())
+                });
+                if continued && !self.seen_toplevel.contains(trimmed_line) {
+                    // "(continued)" only re-opens a category we have seen; anything else is a
+                    // running header and leaves the current category alone
+                } else if has_items {
+                    self.category_path.clear();
+                    self.category_path.push(trimmed_line);
+                    self.header_indents.clear();
+                    self.seen_toplevel.insert(trimmed_line);
+                } else {
+                    report.warnings.push(ParseWarning {
+                        line_number,
+                        reason: WarningReason::CategoryWithoutItems {
+                            content: trimmed_line.to_string(),
+                        },
+                    });
+                }
+            } else if let Some(caps) = RE_ITEM1.captures(line) {
+                // First Level Item/Category ('•')
+                let content = caps.get(1).unwrap().as_str().trim();
+
+                // A '•' indented deeper than an open sub-category header is one of its children
+                // (documents that never switch to 'o'); close every header it is not nested under.
+                // Varieties directly under the commodity and named sub-groups can thus be mixed.
+                let indent = indentation(line);
+                while self
+                    .header_indents
+                    .last()
+                    .is_some_and(|&open| indent <= open)
+                {
+                    self.header_indents.pop();
+                }
+                self.category_path.truncate(1 + self.header_indents.len());
+
+                if self.category_path.is_empty() {
+                    report.warnings.push(ParseWarning {
+                        line_number,
+                        reason: WarningReason::OrphanItem {
+                            content: content.to_string(),
+                        },
+                    });
+                    continue;
+                }
+
+                // A codeless bullet whose next line is one of its children is a header even
+                // without the colon, e.g. "• Watermelon" followed by "  o Mickey Lee (4331)"
+                let has_children = !content.contains(['(', ')', '['])
+                    && !content.chars().any(|c| c.is_ascii_digit())
+                    && next_content_line(&self.lines[index + 1..], self.re_page_marker)
+                        .is_some_and(|next| {
+                            RE_ITEM2.is_match(next)
+                                || (RE_ITEM1.is_match(next) && indentation(next) > indent)
+                        });
+
+                if colon || content.ends_with(':') || has_children {
+                    // Sub-category header like "Watermelon:"
+                    self.category_path
+                        .push(content.trim_end_matches(':').trim());
+                    self.header_indents.push(indent);
+                    // Headers nested past the limit are dropped; their children join the parent
+                    trim_path(
+                        &mut self.category_path,
+                        MAX_CATEGORY_DEPTH,
+                        line_number,
+                        &mut report.warnings,
+                    );
+                    self.header_indents.truncate(self.category_path.len() - 1);
+                } else {
+                    // An item at level 1, or level 2 for an indented '•' child
+                    return self.item_line(line_number, line, content, false);
+                }
+            } else if let Some(caps) = RE_ITEM2.captures(line) {
+                // Second Level Item/Category ('o')
+                let content = caps.get(1).unwrap().as_str().trim();
+
+                // An 'o' item belongs to the innermost open header it is indented under.
+                // The outermost header is kept even at equal indent, since 'o' lines are
+                // often aligned with their '•' header.
+                let indent = indentation(line);
+                while self.header_indents.len() > 1
+                    && self
+                        .header_indents
+                        .last()
+                        .is_some_and(|&open| indent <= open)
+                {
+                    self.header_indents.pop();
+                }
+                self.category_path.truncate(1 + self.header_indents.len());
+
+                if self.category_path.len() < 2 {
+                    // Check if path is exactly Top/SubCategory
+                    report.warnings.push(ParseWarning {
+                        line_number,
+                        reason: WarningReason::UnexpectedPathLength {
+                            content: content.to_string(),
+                            path: self.category_path.iter().map(|c| c.to_string()).collect(),
+                            expected: 2,
+                        },
+                    });
+                    continue; // Skip item
+                }
+                return self.item_line(line_number, line, content, false);
+            } else if let Some(caps) = RE_CATEGORY_ITEM.captures(trimmed_line).filter(|caps| {
+                self.re_toplevel
+                    .is_match(caps.get(1).unwrap().as_str().trim())
+            }) {
+                // "Apple: Akane (4098)" starts the Apple category with Akane as its first item
+                self.category_path.clear();
+                self.category_path
+                    .push(caps.get(1).unwrap().as_str().trim());
+                self.header_indents.clear();
+                return self.item_line(line_number, line, caps.get(2).unwrap().as_str(), false);
+            } else if !self.category_path.is_empty() && patterns.delimited.is_match(trimmed_line) {
+                // Tabular "Name<TAB>code" / "Name: code" rows without a bullet, at the current level
+                return self.item_line(line_number, line, trimmed_line, true);
             }
-            // if content.contains("Cantaloupe / Muskmelon") {
-            //     eprintln!(
-            //         "DEBUG: process_item_line content for Cantaloupe: '{}'",
-            //         content
-            //     );
-            // }
+            // Logging for lines that are neither layout nor items
+            else if !self.re_toplevel.is_match(trimmed_line)
+                && !trimmed_line.contains("retailer assigned")
+            {
+                // Check if it's likely a multi-line characteristic description (heuristic)
+                if !trimmed_line.starts_with('•')
+                    && !trimmed_line.starts_with('o')
+                    && (trimmed_line.starts_with('[') || trimmed_line.ends_with(']'))
+                {
+                    // Potentially part of a previous item's characteristics - harder to parse reliably line-by-line
+                    report.warnings.push(ParseWarning {
+                        line_number,
+                        reason: WarningReason::DetachedCharacteristics {
+                            content: trimmed_line.to_string(),
+                        },
+                    });
+                } else if !trimmed_line.contains(':') && !colon {
+                    // Don't warn for category lines like "Watermelon:"
+                    report.warnings.push(ParseWarning {
+                        line_number,
+                        reason: WarningReason::UnmatchedLine {
+                            content: trimmed_line.to_string(),
+                        },
+                    });
+                    report
+                        .stats
+                        .record_unprocessed(line_number, line, "no item pattern matched");
+                }
+            }
+        }
+        None
+    }
+}
+
+// Lines the parser skips without touching the category path: page markers and running
+// headers, bare OCR page numbers like "42", and "no listing" / "all commodities" notes
+fn is_filler_line(trimmed_line: &str, re_page_marker: &Regex) -> bool {
+    trimmed_line.starts_with("no listing")
+        || trimmed_line.starts_with("all commodities")
+        || re_page_marker.is_match(trimmed_line)
+        || trimmed_line.chars().all(|c| c.is_ascii_digit())
+}
+
+// The first line of `lines` that is neither blank nor filler, for lookahead
+fn next_content_line<'a>(lines: &[&'a str], re_page_marker: &Regex) -> Option<&'a str> {
+    lines.iter().copied().find(|next| {
+        let next = next.trim();
+        !next.is_empty() && !is_filler_line(next, re_page_marker)
+    })
+}
+
+// Width of a line's leading whitespace, counting a tab as four columns
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+// Deepest category path kept: a top-level category plus up to three nested headers,
+// shared by '•' headers and the '•'/'o' items beneath them
+const MAX_CATEGORY_DEPTH: usize = 4;
+
+// Pops `category_path` down to `max_depth` entries, recording a PathTooDeep warning if it had to
+fn trim_path(
+    category_path: &mut Vec<&str>,
+    max_depth: usize,
+    line_number: usize,
+    warnings: &mut Vec<ParseWarning>,
+) {
+    if category_path.len() > max_depth {
+        warnings.push(ParseWarning {
+            line_number,
+            reason: WarningReason::PathTooDeep {
+                path: category_path.iter().map(|c| c.to_string()).collect(),
+                max_depth,
+            },
+        });
+        category_path.truncate(max_depth);
+    }
+}
+
+// Item-line regexes, compiled once and shared by every parse
+static ITEM_PATTERNS: LazyLock<ItemPatterns> = LazyLock::new(ItemPatterns::new);
+
+struct ItemPatterns {
+    // A size group is ", size [pack words] (codes)", e.g. ", large bunch (4065)";
+    // "and"/"&" may stand in for or follow the comma, as in "small (4040) and large (4042)"
+    size_group: Regex,
+    standard: Regex,
+    // "Name (TBD)", "Name (n/a)" and similar placeholder code groups
+    placeholder: Regex,
+    // "Name<TAB>codes" or "Name: codes" as found in tabular exports
+    delimited: Regex,
+    // Any trailing paren group holding at least one digit, used for error recovery
+    malformed_group: Regex,
+    // A size word inside a code group, as in "(small 4049, large 4050)"
+    inline_size: Regex,
+    // Any paren group holding a digit, and an "and"/"&" joining two of its codes
+    code_list: Regex,
+    conjunction: Regex,
+}
+
+impl ItemPatterns {
+    fn new() -> Self {
+        // Allow footnote chars in the code parts of these specific regexes
+        // Uppercase letters are allowed so letter-suffixed variants like "4011A" reach the code parser
+        ItemPatterns {
+            // A slash separates alternative codes, "(4053/4958)"; each becomes a code of the item
+            size_group: Regex::new(r"(?:,\s*(?:and\s+|&\s*)?|\s+(?:and|&)\s+)(small|medium|large|extra large|jumbo)((?:\s+[a-z]+)*)\s*\(([\d,/\s¹²³\-‐–—A-Z]+)\)").unwrap(),
+            // Greedy name so the code group is always the last paren group on the line
+            standard: Regex::new(r"^(.*)\(([\d,/\s\-‐–—¹²³A-Z]+)\)$").unwrap(),
+            placeholder: Regex::new(r"(?i)^(.*?)\s*\(\s*(?:tbd|tba|n/?a|pending|\?+)\s*\)$").unwrap(),
+            delimited: Regex::new(r"^([^\t:]+?)\s*(?:\t+|:)\s*(\d[\d,\s¹²³A-Z]*)$").unwrap(),
+            malformed_group: Regex::new(r"^(.*?)\s*\(([^()]*\d[^()]*)\)$").unwrap(),
+            inline_size: Regex::new(r"\b(?:extra large|small|medium|large|jumbo)\b").unwrap(),
+            code_list: Regex::new(r"\(([^()]*\d[^()]*)\)").unwrap(),
+            conjunction: Regex::new(r"(?:,\s*|\s+)(?:and|&)\s+(\d)").unwrap(),
+        }
+    }
+}
+
+// True for tokens the code parser understands, e.g. "4011", "41361", "4011A"
+fn is_code_token(token: &str) -> bool {
+    token
+        .chars()
+        .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase() || "¹²³-‐–— ".contains(c))
+}
+
+// One ", size [pack] (codes)" segment of a split-size line
+struct SizeGroup<'a> {
+    size: &'a str,
+    pack: &'a str,
+    codes: &'a str,
+    // "C / D" when the group is preceded by its own aliased name, as in
+    // "A / B, small (3001), C / D, large (3290)"
+    rename: Option<&'a str>,
+}
+
+// Helper to split a code group with inline sizes, "small 4049, 43181, large 4050", into
+// (size, codes) pairs. The group must start with a size word and every size needs codes.
+fn split_inline_sizes<'a>(inner: &'a str, re_size_word: &Regex) -> Option<Vec<(&'a str, &'a str)>> {
+    let inner = inner.trim();
+    let words: Vec<_> = re_size_word.find_iter(inner).collect();
+    if words.first()?.start() != 0 {
+        return None;
+    }
+    let mut groups = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        let end = words.get(i + 1).map_or(inner.len(), |next| next.start());
+        let codes = inner[word.end()..end].trim().trim_end_matches(',').trim();
+        let mut tokens = codes.split(',').map(str::trim);
+        if codes.is_empty() || !tokens.all(|token| !token.is_empty() && is_code_token(token)) {
+            return None;
+        }
+        groups.push((word.as_str(), codes));
+    }
+    Some(groups)
+}
+
+// Helper to split a trailing "[a, b]" that follows a code group off a line,
+// e.g. "Kiwi, small (4030), large (4301) [fuzzy]" -> ("Kiwi, ... (4301)", ["fuzzy"])
+fn split_trailing_characteristics(content: &str) -> (&str, Vec<String>) {
+    if let Some(rest) = content.strip_suffix(']')
+        && let Some(open) = rest.rfind('[')
+        && rest[..open].trim_end().ends_with(')')
+    {
+        let characteristics = rest[open + 1..]
+            .split(',')
+            .map(|characteristic| characteristic.trim().to_string())
+            .filter(|characteristic| !characteristic.is_empty())
+            .collect();
+        return (rest[..open].trim_end(), characteristics);
+    }
+    (content, Vec::new())
+}
+
+// Helper to split "Name, size (codes), size pack (codes), ..." into the base name and
+// its size groups. Only matches when the line ends with two or more size groups that
+// are back to back or separated by a per-size "Name / Alias" segment.
+fn split_size_groups<'a>(
+    content: &'a str,
+    re_size_group: &Regex,
+) -> Option<(&'a str, Vec<SizeGroup<'a>>)> {
+    let mut base_end = None;
+    let mut last_end = 0;
+    let mut groups = Vec::new();
+
+    for caps in re_size_group.captures_iter(content) {
+        let whole = caps.get(0).unwrap();
+        let mut rename = None;
+        match base_end {
+            None => base_end = Some(whole.start()),
+            Some(_) if whole.start() != last_end => {
+                // Only a "Name / Alias" segment may sit between two groups
+                let gap = content[last_end..whole.start()]
+                    .trim_start_matches(',')
+                    .trim();
+                if !gap.contains('/') || gap.contains(['(', ')', ',']) {
+                    return None;
+                }
+                rename = Some(gap);
+            }
+            Some(_) => {}
         }
+        last_end = whole.end();
+        groups.push(SizeGroup {
+            size: caps.get(1).unwrap().as_str(),
+            pack: caps.get(2).unwrap().as_str(),
+            codes: caps.get(3).unwrap().as_str(),
+            rename,
+        });
+    }
+
+    if groups.len() < 2 || last_end != content.len() {
+        return None;
     }
+    base_end.map(|end| (&content[..end], groups))
+}
 
-    Ok(PluCollection { items })
+// Splits `text` on `separator`, ignoring separators inside (...) or [...] groups.
+// Empty segments are dropped.
+fn split_outside_brackets(text: &str, separator: char) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if c == separator && depth == 0 => {
+                segments.push(text[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    segments.push(text[start..].trim());
+    segments.retain(|segment| !segment.is_empty());
+    segments
 }
 
 // Ensure process_item_line returns Ok(false) if no pattern matches
 fn process_item_line(
     content: &str,
-    category_path: &VecDeque<String>,
-    re_size_split: &Regex,
-    re_alt_size_split: &Regex,
-    re_standard: &Regex,
-    re_range: &Regex, // Added parameter
-    items: &mut Vec<PluItem>,
+    category_path: &[&str],
+    line_number: usize,
+    patterns: &ItemPatterns,
+    options: &ParseOptions,
+    report: &mut ParseReport,
 ) -> Result<bool, String> {
+    // Several quick items on one line, e.g. "Lime (4048); Lemon (4053)"
+    let segments = split_outside_brackets(content, ';');
+    if segments.len() > 1 {
+        let mut failed = Vec::new();
+        for &segment in &segments {
+            if !process_item_line(
+                segment,
+                category_path,
+                line_number,
+                patterns,
+                options,
+                report,
+            )? {
+                failed.push(segment);
+            }
+        }
+        // When no segment matched, the caller records the whole line instead
+        let processed = failed.len() < segments.len();
+        if processed {
+            for segment in failed {
+                report.warnings.push(ParseWarning {
+                    line_number,
+                    reason: WarningReason::UnmatchedLine {
+                        content: segment.trim().to_string(),
+                    },
+                });
+                report.stats.record_unprocessed(
+                    line_number,
+                    segment.trim(),
+                    "no item pattern matched",
+                );
+            }
+        }
+        return Ok(processed);
+    }
+
+    // Sentence punctuation after the code group, as in "Akane, small (4098)."
+    let unpunctuated = content.trim_end_matches(['.', ';', ',', ':']).trim_end();
+    let content = if unpunctuated.ends_with([')', ']']) {
+        unpunctuated
+    } else {
+        content
+    };
+
+    // Tidy prose-style code lists such as "(4011, 4012,)" or "(4011, 4012, and 4013)"
+    let content = &*patterns
+        .code_list
+        .replace_all(content, |caps: &regex::Captures| {
+            let inner = patterns.conjunction.replace_all(&caps[1], ", ${1}");
+            format!("({})", inner.trim().trim_end_matches(',').trim_end())
+        });
+
+    // Locale-specific code formats are rewritten into plain digit lists first
+    let content = &*match &options.code_pattern {
+        Some(re_code) => patterns
+            .code_list
+            .replace_all(content, |caps: &regex::Captures| {
+                let tokens = extract_code_tokens(&caps[1], re_code);
+                if tokens.is_empty() {
+                    caps[0].to_string()
+                } else {
+                    format!("({})", tokens.join(", "))
+                }
+            }),
+        None => Cow::Borrowed(content),
+    };
+
     if content.contains("retailer assigned") {
-        return Ok(true); // Processed (ignored)
+        // Keep the reserved block(s) so callers can check codes against them
+        report
+            .collection
+            .retailer_ranges
+            .extend(extract_code_ranges(content));
+        return Ok(true); // Processed, no item
+    }
+
+    // Draft documents use placeholders such as "(TBD)" where no code is assigned yet
+    if let Some(caps) = patterns.placeholder.captures(content) {
+        let name_part = caps.get(1).unwrap().as_str().trim();
+        let mut item = build_standard_item(name_part, "", Vec::new(), category_path);
+        item.pending = true;
+        report.collection.items.push(item);
+        return Ok(true);
+    }
+
+    // Sizes written inside one code group, e.g. "Melon (small 4049, large 4050)"
+    if let Some(caps) = patterns.malformed_group.captures(content)
+        && let Some(groups) =
+            split_inline_sizes(caps.get(2).unwrap().as_str(), &patterns.inline_size)
+    {
+        let name_part = caps.get(1).unwrap().as_str().trim();
+        for (size, codes_str) in groups {
+            let size = normalize_size(size);
+            let mut item = build_standard_item(
+                name_part,
+                codes_str,
+                parse_plu_codes(codes_str),
+                category_path,
+            );
+            item.name = format!("{}, {}", item.name, size);
+            item.alternative_name = item
+                .alternative_name
+                .map(|alt| format!("{}, {}", alt, size));
+            item.size = Some(size);
+            report.collection.items.push(item);
+        }
+        return Ok(true);
     }
 
-    // Try matching "Name, size (codes), size (codes)" pattern first
-    if let Some(caps) = re_alt_size_split.captures(content) {
-        // ... (parsing logic for split size) ...
+    // Try matching "Name, size (codes), size (codes)" pattern
+    // A "[...]" after the last size group applies to every size, e.g. "..., large (4301) [fuzzy]"
+    let (sized_content, trailing_characteristics) = split_trailing_characteristics(content);
+    if let Some((base_name_part, groups)) = split_size_groups(sized_content, &patterns.size_group) {
         // Code parsing relies on the updated parse_plu_codes
-        let base_name_part = caps.get(1).unwrap().as_str().trim();
-        let size1_str = caps.get(2).unwrap().as_str();
-        let codes1_str = caps.get(3).unwrap().as_str();
-        let size2_str = caps.get(4).unwrap().as_str();
-        let codes2_str = caps.get(5).unwrap().as_str();
-
-        let codes1 = parse_plu_codes(codes1_str);
-        let codes2 = parse_plu_codes(codes2_str);
-
-        // ... (rest of split size item creation) ...
-        let (name_no_chars, characteristics) = extract_characteristics(base_name_part);
-        let (name1, alt_name1) = extract_alternative_name(&name_no_chars);
-        let alt_name2 = alt_name1.clone();
-
-        let size1 = normalize_size(size1_str);
-        let size2 = normalize_size(size2_str);
-
-        let final_name1 = format!("{}, {}", name1.trim(), size1);
-        let final_name2 = format!("{}, {}", name1.trim(), size2);
-
-        if !codes1.is_empty() {
-            items.push(PluItem::new(
-                final_name1,
-                codes1,
-                category_path.iter().cloned().collect(),
-                alt_name1.map(|a| format!("{}, {}", a.trim(), size1)),
+        let (base_name_part, sold_by) = extract_sold_by(strip_trailing_etc(base_name_part.trim()));
+        let (name_no_chars, mut characteristics) = extract_characteristics(&base_name_part);
+        characteristics.extend(trailing_characteristics);
+        let (name_no_chars, composition) = extract_composition(&name_no_chars);
+        let (name_no_alias, paren_alias) = extract_paren_alias(&name_no_chars);
+        characteristics.extend(extract_paren_qualifiers(&name_no_alias));
+        // The alt name applies to every size group until a group brings its own
+        let (base_name, alt_name, mut more_alt_names) = extract_alternative_name(&name_no_alias);
+        let mut alt_name = alt_name.or(paren_alias);
+        let (mut base_name, ripeness) = extract_ripeness(&base_name, category_path);
+        let sizes = groups.len();
+        let items_before = report.collection.items.len();
+
+        for group in groups {
+            if let Some(rename) = group.rename {
+                (base_name, alt_name, more_alt_names) = extract_alternative_name(rename);
+            }
+            let codes = parse_plu_codes(group.codes);
+            if codes.is_empty() {
+                continue;
+            }
+
+            // Pack descriptors keep repeated sizes apart, e.g. "large" vs "large bunch"
+            let size = normalize_size(group.size);
+            let label = std::iter::once(size.as_str())
+                .chain(group.pack.split_whitespace())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let mut item = PluItem::new(
+                format!("{}, {}", base_name.trim(), label),
+                codes,
+                category_path.iter().map(|c| c.to_string()).collect(),
+                alt_name
+                    .as_ref()
+                    .map(|a| format!("{}, {}", a.trim(), label)),
                 characteristics.clone(),
-                Some(size1),
-            ));
+                Some(size),
+            );
+            item.composition = composition.clone();
+            item.ripeness = ripeness.clone();
+            item.sold_by = sold_by.clone();
+            item.alternative_names = more_alt_names
+                .iter()
+                .map(|alias| format!("{}, {}", alias, label))
+                .collect();
+            annotate_item(&mut item, group.codes);
+            report.collection.items.push(item);
         }
-        if !codes2.is_empty() {
-            items.push(PluItem::new(
-                final_name2,
-                codes2,
-                category_path.iter().cloned().collect(),
-                alt_name2.map(|a| format!("{}, {}", a.trim(), size2)),
-                characteristics,
-                Some(size2),
-            ));
+        // Every size group should yield exactly one item
+        let items = report.collection.items.len() - items_before;
+        if items != sizes {
+            report.warnings.push(ParseWarning {
+                line_number,
+                reason: WarningReason::SizeCountMismatch {
+                    content: content.to_string(),
+                    sizes,
+                    items,
+                },
+            });
         }
-        // Ensure we return true only if at least one item was added? Or just if pattern matched.
-        // Let's return true if the pattern matched, even if codes were empty (e.g. range)
+        // Return true if the pattern matched, even if codes were empty (e.g. range)
         Ok(true)
-    } else if let Some(caps) = re_standard.captures(content) {
+    } else if let Some(caps) = patterns.standard.captures(content) {
         // Standard "Name (codes)" pattern
         let name_part = caps.get(1).unwrap().as_str().trim();
         let codes_str = caps.get(2).unwrap().as_str();
@@ -338,45 +1411,84 @@ fn process_item_line(
         let codes = parse_plu_codes(codes_str);
 
         if !codes.is_empty() {
-            // ... (item creation logic) ...
-            let (name_no_chars, characteristics) = extract_characteristics(name_part);
-            let (name, alternative_name) = extract_alternative_name(&name_no_chars);
-            let final_name = name;
-            let mut size = None;
-            let re_size_suffix =
-                Regex::new(r"^(.*?),\s*(small|medium|large|extra large|jumbo)$").unwrap();
-
-            let mut my_final_name: String = final_name.clone();
-
-            if let Some(size_caps) = re_size_suffix.captures(&final_name) {
-                // Capture on the mutable name
-                my_final_name = size_caps.get(1).unwrap().as_str().trim().to_string();
-                size = Some(normalize_size(size_caps.get(2).unwrap().as_str()));
-            } else {
-                // If no specific size suffix is found, the my_final_name (which was initialized from final_name)
-                // might still contain a general size descriptor that should be removed if it's the whole name.
-                // This part is tricky; for now, ensure my_final_name is used.
-                // Example: "Apple" vs "Apple, small". If "Apple, small", it's split. If "Apple", it's kept.
-                // If the name itself is "Small" (unlikely for PLU but as an example), it should not be cleared.
-                // The current logic is: if final_name is "Foo, small", my_final_name becomes "Foo" and size becomes "small".
-                // If final_name is "Foo", my_final_name remains "Foo" and size remains None. This is correct.
-            }
-
-            items.push(PluItem::new(
-                my_final_name,
+            report.collection.items.push(build_standard_item(
+                name_part,
+                codes_str,
                 codes,
-                category_path.iter().cloned().collect(),
-                alternative_name,
-                characteristics,
-                size,
+                category_path,
             ));
-
             Ok(true) // Processed
+        } else if !extract_code_ranges(codes_str).is_empty() {
+            // Range-only entries such as reserved blocks keep their ranges in `code_ranges`
+            report.collection.items.push(build_standard_item(
+                name_part,
+                codes_str,
+                codes,
+                category_path,
+            ));
+            Ok(true)
         } else {
-            // Pattern matched, but no codes found (e.g., it was a range, or just text in parens)
+            // Pattern matched, but no codes found (e.g. just text in parens)
             // Avoid "Unprocessed line" warning for these cases.
+            if codes_str.trim().is_empty() {
+                // "Foo ( )" has nothing to salvage, but shouldn't vanish without a trace
+                report
+                    .stats
+                    .record_unprocessed(line_number, content, "empty code group");
+            }
             Ok(true) // Mark as processed
         }
+    } else if let Some(caps) = patterns.delimited.captures(content) {
+        // Tab or colon separated "Name<TAB>codes" / "Name: codes"
+        let name_part = caps.get(1).unwrap().as_str().trim();
+        let codes_str = caps.get(2).unwrap().as_str();
+        let codes = parse_plu_codes(codes_str);
+        // Only a column of 4- or 5-digit codes makes a row; "Page: 3" is no item
+        if codes.is_empty() || !codes.iter().all(|code| (1000..=99999).contains(code)) {
+            return Ok(false);
+        }
+        report.collection.items.push(build_standard_item(
+            name_part,
+            codes_str,
+            codes,
+            category_path,
+        ));
+        Ok(true)
+    } else if let Some(caps) = patterns.malformed_group.captures(content) {
+        // Code group polluted by stray tokens, e.g. "(4011, abc, 4012)"
+        let name_part = caps.get(1).unwrap().as_str().trim();
+        let group = caps.get(2).unwrap().as_str();
+        let (valid, invalid): (Vec<&str>, Vec<&str>) = group
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .partition(|token| is_code_token(token));
+
+        match options.recovery {
+            RecoveryMode::Lenient => Ok(false),
+            RecoveryMode::Strict => Err(format!(
+                "Line {}: malformed code group '({})' in '{}'",
+                line_number, group, content
+            )),
+            RecoveryMode::Recover => {
+                report.stats.record_unprocessed(
+                    line_number,
+                    content,
+                    &format!("malformed code tokens: {}", invalid.join(", ")),
+                );
+                let salvaged = valid.join(", ");
+                let codes = parse_plu_codes(&salvaged);
+                if !codes.is_empty() {
+                    report.collection.items.push(build_standard_item(
+                        name_part,
+                        &salvaged,
+                        codes,
+                        category_path,
+                    ));
+                }
+                Ok(true)
+            }
+        }
     } else {
         // Line didn't match any item pattern we expect
         // This might include the Cantaloupe line if the regex fails
@@ -385,103 +1497,130 @@ fn process_item_line(
     }
 }
 
-/////////////////////////////////////////////////////////////////
-// Helper function to process a line containing item data
-fn process_item_line_bak(
-    content: &str,
-    category_path: &VecDeque<String>,
-    re_size_split: &Regex,
-    re_alt_size_split: &Regex,
-    re_standard: &Regex,
-    items: &mut Vec<PluItem>,
-) -> Result<bool, String> {
-    if content.contains("retailer assigned") {
-        // Optionally parse specific retailer codes if needed, otherwise skip
-        return Ok(true); // Mark as processed
-    }
+// Pulls codes out of a code group with a user-supplied pattern, joining each match's
+// capture groups (or the whole match) and keeping only the digits
+fn extract_code_tokens(group: &str, re_code: &Regex) -> Vec<String> {
+    re_code
+        .captures_iter(group)
+        .map(|caps| {
+            let parts: Vec<&str> = if caps.len() > 1 {
+                caps.iter().skip(1).flatten().map(|m| m.as_str()).collect()
+            } else {
+                vec![caps.get(0).unwrap().as_str()]
+            };
+            parts
+                .concat()
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect::<String>()
+        })
+        .filter(|token| !token.is_empty())
+        .collect()
+}
 
-    // Try matching "Name, size (codes), size (codes)" pattern first
-    if let Some(caps) = re_size_split
-        .captures(content)
-        .or_else(|| re_alt_size_split.captures(content))
+// Fills in the fields derived from an item's name and raw code group
+fn annotate_item(item: &mut PluItem, codes_str: &str) {
+    if let Some(name) = strip_trademarks(&item.name) {
+        item.name = name;
+        item.trademarked = true;
+    }
+    if let Some(alt) = item.alternative_name.as_deref().and_then(strip_trademarks) {
+        item.alternative_name = Some(alt);
+        item.trademarked = true;
+    }
+    item.variant_suffix = extract_variant_suffix(codes_str);
+    item.footnotes = extract_footnotes(codes_str);
+    item.code_ranges = extract_code_ranges(codes_str);
+    // Numeric codes drop leading zeros, so keep the written form when there are any
+    static RE_NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+").unwrap());
+    let written: Vec<&str> = RE_NUMBER.find_iter(codes_str).map(|m| m.as_str()).collect();
+    if written
+        .iter()
+        .any(|code| code.len() > 1 && code.starts_with('0'))
     {
-        let base_name_part = caps.get(1).unwrap().as_str().trim();
-        let size1_str = caps.get(2).unwrap().as_str();
-        let codes1_str = caps.get(3).unwrap().as_str();
-        let size2_str = caps.get(4).unwrap().as_str();
-        let codes2_str = caps.get(5).unwrap().as_str();
-
-        let codes1 = parse_plu_codes(codes1_str);
-        let codes2 = parse_plu_codes(codes2_str);
-
-        // Process base name for characteristics and alt names
-        let (name_no_chars, characteristics) = extract_characteristics(base_name_part);
-        let (name1, alt_name1) = extract_alternative_name(&name_no_chars);
-        // Assume alt name applies to both sizes if present
-        let alt_name2 = alt_name1.clone();
-
-        let size1 = normalize_size(size1_str);
-        let size2 = normalize_size(size2_str);
-
-        let final_name1 = format!("{}, {}", name1.trim(), size1);
-        let final_name2 = format!("{}, {}", name1.trim(), size2); // Use same base name
-
-        if !codes1.is_empty() {
-            items.push(PluItem::new(
-                final_name1,
-                codes1,
-                category_path.iter().cloned().collect(),
-                alt_name1.map(|a| format!("{}, {}", a.trim(), size1)), // Append size to alt name too
-                characteristics.clone(),
-                Some(size1),
-            ));
-        }
-        if !codes2.is_empty() {
-            items.push(PluItem::new(
-                final_name2,
-                codes2,
-                category_path.iter().cloned().collect(),
-                alt_name2.map(|a| format!("{}, {}", a.trim(), size2)),
-                characteristics, // Reuse characteristics
-                Some(size2),
-            ));
-        }
-        Ok(true)
-    } else if let Some(caps) = re_standard.captures(content) {
-        // Standard "Name (codes)" pattern
-        let name_part = caps.get(1).unwrap().as_str().trim();
-        let codes_str = caps.get(2).unwrap().as_str();
-
-        let codes = parse_plu_codes(codes_str);
+        item.code_strings = written.into_iter().map(String::from).collect();
+    }
+    item.color = extract_color(&item.name);
+    item.components = extract_components(&item.name);
+    static RE_ORGANIC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\borganic\b").unwrap());
+    if RE_ORGANIC.is_match(&item.name) {
+        item.is_organic = true;
+    }
+    // An explicit "conventional" label wins over every organic heuristic
+    static RE_CONVENTIONAL: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?i),\s*conventional\b|\s*\(\s*conventional\s*\)").unwrap());
+    if RE_CONVENTIONAL.is_match(&item.name) {
+        item.name = RE_CONVENTIONAL
+            .replace_all(&item.name, "")
+            .trim()
+            .to_string();
+        item.characteristics
+            .retain(|characteristic| !characteristic.eq_ignore_ascii_case("conventional"));
+        item.is_organic = false;
+    }
+}
 
-        if !codes.is_empty() {
-            let (name_no_chars, characteristics) = extract_characteristics(name_part);
-            let (name, alternative_name) = extract_alternative_name(&name_no_chars);
-
-            // Attempt to infer size from the name part if not split earlier
-            let mut final_name = name;
-            let mut size = None;
-            let re_size_suffix =
-                Regex::new(r"^(.*?),\s*(small|medium|large|extra large|jumbo)$").unwrap();
-            if let Some(size_caps) = re_size_suffix.captures(&final_name) {
-                let my_final_name = size_caps.get(1).unwrap().as_str().trim().to_string();
-                size = Some(normalize_size(size_caps.get(2).unwrap().as_str()));
-            }
+// Removes ™, ® and © marks, returning None when the text has none
+fn strip_trademarks(text: &str) -> Option<String> {
+    const MARKS: [char; 3] = ['™', '®', '©'];
+    if !text.contains(MARKS) {
+        return None;
+    }
+    let stripped: String = text.chars().filter(|c| !MARKS.contains(c)).collect();
+    Some(stripped.split_whitespace().collect::<Vec<_>>().join(" "))
+}
 
-            items.push(PluItem::new(
-                final_name,
-                codes,
-                category_path.iter().cloned().collect(),
-                alternative_name,
-                characteristics,
-                size,
-            ));
-        }
-        Ok(true)
+// Builds an item from a standard "Name (codes)" match
+fn build_standard_item(
+    name_part: &str,
+    codes_str: &str,
+    codes: Vec<u32>,
+    category_path: &[&str],
+) -> PluItem {
+    let (name_part, sold_by) = extract_sold_by(strip_trailing_etc(name_part));
+    let (name_no_chars, mut characteristics) = extract_characteristics(&name_part);
+    let (name_no_chars, composition) = extract_composition(&name_no_chars);
+    let (name_no_alias, paren_alias) = extract_paren_alias(&name_no_chars);
+    characteristics.extend(extract_paren_qualifiers(&name_no_alias));
+    let (name, alternative_name, alternative_names) = extract_alternative_name(&name_no_alias);
+    let alternative_name = alternative_name.or(paren_alias);
+    let final_name = name;
+    let mut size = None;
+    static RE_SIZE_SUFFIX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(.*?),\s*(small|medium|large|extra large|jumbo)$").unwrap());
+
+    let mut my_final_name: String = final_name.clone();
+
+    if let Some(size_caps) = RE_SIZE_SUFFIX.captures(&final_name) {
+        // Capture on the mutable name
+        my_final_name = size_caps.get(1).unwrap().as_str().trim().to_string();
+        size = Some(normalize_size(size_caps.get(2).unwrap().as_str()));
     } else {
-        // Line doesn't match item patterns we expect
-        Ok(false)
+        // If no specific size suffix is found, the my_final_name (which was initialized from final_name)
+        // might still contain a general size descriptor that should be removed if it's the whole name.
+        // This part is tricky; for now, ensure my_final_name is used.
+        // Example: "Apple" vs "Apple, small". If "Apple, small", it's split. If "Apple", it's kept.
+        // If the name itself is "Small" (unlikely for PLU but as an example), it should not be cleared.
+        // The current logic is: if final_name is "Foo, small", my_final_name becomes "Foo" and size becomes "small".
+        // If final_name is "Foo", my_final_name remains "Foo" and size remains None. This is correct.
     }
+    // Ripeness sits before the size, e.g. "Avocado, ripe, large"
+    let (my_final_name, ripeness) = extract_ripeness(&my_final_name, category_path);
+
+    let mut item = PluItem::new(
+        my_final_name,
+        codes,
+        category_path.iter().map(|c| c.to_string()).collect(),
+        alternative_name,
+        characteristics,
+        size,
+    );
+    item.composition = composition;
+    item.ripeness = ripeness;
+    item.sold_by = sold_by;
+    item.alternative_names = alternative_names;
+    annotate_item(&mut item, codes_str);
+    item
 }
 
 // Example usage (add to main.rs or tests)
@@ -670,7 +1809,7 @@ mod tests {
     fn test_parse_with_footnote() {
         // Note: Footnote ³ is ignored
         let text = "Asparagus\n• Green, small (4080), large (4521), bunch (33923)";
-        let collection = parse_plu_text(text).unwrap();
+        let _collection = parse_plu_text(text).unwrap();
         // This complex line isn't handled by the simple size split regex.
         // It will likely fall back to the standard pattern matching.
         // Let's see how it parses "bunch (33923)"
@@ -681,16 +1820,38 @@ mod tests {
         // RETHINK: The current regexes might struggle here. The re_size_split expects exactly two size groups.
         // Let's try a simpler line first.
         let text_simple = "Asparagus\n• White, small (4522), large (4523)";
-        let collection_simple = parse_plu_text(&text_simple).unwrap();
+        let collection_simple = parse_plu_text(text_simple).unwrap();
         assert_eq!(collection_simple.items.len(), 2); // Should work
 
         let text_bunch = "Asparagus\n• Green, bunch (33923)"; // Single item variation
-        let collection_bunch = parse_plu_text(&text_bunch).unwrap();
+        let collection_bunch = parse_plu_text(text_bunch).unwrap();
         assert_eq!(collection_bunch.items.len(), 1);
         assert_eq!(collection_bunch.items[0].name, "Green, bunch"); // Name includes size/type
         assert_eq!(collection_bunch.items[0].plu_codes, vec![3392]);
         assert_eq!(collection_bunch.items[0].category_path, vec!["Asparagus"]);
     }
+    #[test]
+    fn test_fraction_slash_is_not_alternative_name() {
+        let text = "Herbs\n• Herbs, 1/2 bunch (4889)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 1);
+        assert_eq!(collection.items[0].name, "Herbs, 1/2 bunch");
+        assert_eq!(collection.items[0].alternative_name, None);
+        assert_eq!(collection.items[0].plu_codes, vec![4889]);
+    }
+
+    #[test]
+    fn test_parse_letter_suffixed_code() {
+        let text = "Banana\n• Cavendish (4011A)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 1);
+        assert_eq!(collection.items[0].plu_codes, vec![4011]);
+        assert_eq!(collection.items[0].variant_suffix, Some("A".to_string()));
+
+        let plain = parse_plu_text("Banana\n• Cavendish (4011)").unwrap();
+        assert_eq!(plain.items[0].variant_suffix, None);
+    }
+
     #[test]
     fn test_parse_multi_code_single_item() {
         let text = "Apple\n• Golden Delicious, small (4021, 41361,2), large (4020, 41371,2)";
@@ -712,4 +1873,1272 @@ mod tests {
             .unwrap();
         assert_eq!(large.plu_codes, vec![4020, 4137]);
     }
+
+    #[test]
+    fn test_parse_flat_mode() {
+        let text = "Akane (4098)\nMickey Lee / Sugarbaby (4331)\nCantaloupe, small (4049)";
+        let options = ParseOptions {
+            flat: true,
+            ..Default::default()
+        };
+        let collection = parse_plu_text_with_options(text, &options).unwrap();
+        assert_eq!(collection.items.len(), 3);
+        assert_eq!(collection.items[0].name, "Akane");
+        assert_eq!(
+            collection.items[1].alternative_name,
+            Some("Sugarbaby".to_string())
+        );
+        assert_eq!(collection.items[2].size, Some("small".to_string()));
+        assert!(collection.items.iter().all(|i| i.category_path.is_empty()));
+
+        // Without flat mode the same lines have no category to belong to
+        assert!(parse_plu_text(text).unwrap().items.is_empty());
+    }
+
+    #[test]
+    fn test_extract_color() {
+        let collection = parse_plu_text("Apple\n• Red Delicious (4015)\n• Akane (4098)").unwrap();
+        assert_eq!(collection.items[0].name, "Red Delicious");
+        assert_eq!(collection.items[0].color, Some("red".to_string()));
+        assert_eq!(collection.items[1].color, None);
+    }
+
+    #[test]
+    fn test_parse_non_code_parenthetical() {
+        let text = "Kiwifruit\n• Kiwi (fuzzy) (4030)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 1);
+        assert_eq!(collection.items[0].name, "Kiwi (fuzzy)");
+        assert_eq!(collection.items[0].characteristics, vec!["fuzzy"]);
+        assert_eq!(collection.items[0].plu_codes, vec![4030]);
+    }
+
+    #[test]
+    fn test_parse_repeated_size_with_pack_types() {
+        let text = "Tomato\n• Tomato, large (4064), large bunch (4065)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 2);
+        assert_eq!(collection.items[0].name, "Tomato, large");
+        assert_eq!(collection.items[0].plu_codes, vec![4064]);
+        assert_eq!(collection.items[1].name, "Tomato, large bunch");
+        assert_eq!(collection.items[1].plu_codes, vec![4065]);
+        assert_eq!(collection.items[1].size, Some("large".to_string()));
+        assert_ne!(collection.items[0].name, collection.items[1].name);
+    }
+
+    #[test]
+    fn test_parse_three_size_groups() {
+        let text = "Onion\n• Yellow, small (4093), medium (4663), jumbo (4665)";
+        let collection = parse_plu_text(text).unwrap();
+        let names: Vec<_> = collection.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Yellow, small", "Yellow, medium", "Yellow, jumbo"]
+        );
+        assert_eq!(collection.items[2].plu_codes, vec![4665]);
+    }
+
+    #[test]
+    fn test_recover_malformed_code_group() {
+        let text = "Banana\n• Cavendish (4011, abc, 4012)";
+        let options = ParseOptions {
+            recovery: RecoveryMode::Recover,
+            ..Default::default()
+        };
+        let report = parse_plu_report(text, &options).unwrap();
+        assert_eq!(report.collection.items.len(), 1);
+        assert_eq!(report.collection.items[0].name, "Cavendish");
+        assert_eq!(report.collection.items[0].plu_codes, vec![4011, 4012]);
+        assert_eq!(report.stats.unprocessed_lines.len(), 1);
+        assert_eq!(report.stats.unprocessed_lines[0].line_number, 2);
+        assert!(report.stats.unprocessed_lines[0].reason.contains("abc"));
+
+        // Lenient (default) skips the line but still lists it, strict refuses it
+        let lenient = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert!(lenient.collection.items.is_empty());
+        assert_eq!(lenient.stats.unprocessed_lines.len(), 1);
+        assert_eq!(
+            lenient.stats.unprocessed_lines[0].reason,
+            "no item pattern matched"
+        );
+        let strict = ParseOptions {
+            recovery: RecoveryMode::Strict,
+            ..Default::default()
+        };
+        assert!(parse_plu_report(text, &strict).is_err());
+    }
+
+    #[test]
+    fn test_parse_tab_and_colon_delimited() {
+        let text = "Apple\nAkane\t4098\nFuji: 4131\n• Gala: 4133\n• Cooking:\n  o Bramley (3349)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 4);
+        assert_eq!(collection.items[0].name, "Akane");
+        assert_eq!(collection.items[0].plu_codes, vec![4098]);
+        assert_eq!(collection.items[0].category_path, vec!["Apple"]);
+        assert_eq!(collection.items[1].name, "Fuji");
+        assert_eq!(collection.items[1].plu_codes, vec![4131]);
+        assert_eq!(collection.items[2].name, "Gala");
+        // A bare trailing colon is still a sub-category header
+        assert_eq!(collection.items[3].category_path, vec!["Apple", "Cooking"]);
+
+        // Rows without a usable code column are reported, not turned into items
+        let report =
+            parse_plu_report("Apple\nPage: 3\nFuji: 4131", &ParseOptions::default()).unwrap();
+        assert_eq!(report.collection.items.len(), 1);
+        assert_eq!(report.collection.items[0].name, "Fuji");
+        assert_eq!(
+            report.warnings,
+            vec![ParseWarning {
+                line_number: 2,
+                reason: WarningReason::UnmatchedLine {
+                    content: "Page: 3".into()
+                }
+            }]
+        );
+        assert_eq!(report.stats.unprocessed_lines.len(), 1);
+    }
+
+    #[test]
+    fn test_path_warnings_are_collected() {
+        // Headers nested past MAX_CATEGORY_DEPTH are dropped with a warning
+        let text = "Melon\n• Watermelon:\n  • Seedless:\n    • Mini:\n      • Striped:\n        o Tiger (3422)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            report.warnings,
+            vec![ParseWarning {
+                line_number: 5,
+                reason: WarningReason::PathTooDeep {
+                    path: vec![
+                        "Melon".into(),
+                        "Watermelon".into(),
+                        "Seedless".into(),
+                        "Mini".into(),
+                        "Striped".into()
+                    ],
+                    max_depth: 4,
+                },
+            }]
+        );
+        assert_eq!(
+            report.collection.items[0].category_path,
+            vec!["Melon", "Watermelon", "Seedless", "Mini"]
+        );
+
+        let report = parse_plu_report(
+            "• Akane (4098)\nMelon\n  o Mini (3421)",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert!(report.collection.items.is_empty());
+        assert_eq!(report.warnings.len(), 2);
+        assert_eq!(report.warnings[0].line_number, 1);
+        assert!(matches!(
+            report.warnings[0].reason,
+            WarningReason::OrphanItem { .. }
+        ));
+        assert_eq!(report.warnings[1].line_number, 3);
+        assert!(matches!(
+            report.warnings[1].reason,
+            WarningReason::UnexpectedPathLength { expected: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_custom_toplevel_pattern() {
+        let text = "Jalapeño\n• Green (4693)";
+        assert!(parse_plu_text(text).unwrap().items.is_empty());
+
+        let options = ParseOptions {
+            toplevel_pattern: Some(Regex::new(r"^\p{Lu}[\p{L}\d /&'-]+$").unwrap()),
+            ..Default::default()
+        };
+        let collection = parse_plu_text_with_options(text, &options).unwrap();
+        assert_eq!(collection.items.len(), 1);
+        assert_eq!(collection.items[0].category_path, vec!["Jalapeño"]);
+    }
+
+    #[test]
+    fn test_indented_bullet_children() {
+        let text = "Melon
+• Watermelon:
+    • Mickey Lee / Sugarbaby (4331)
+    • Mini, seedless (3421)
+• Honeydew (4034)
+• Cantaloupe:
+\t• Tuscan (4319)";
+        let collection = parse_plu_text(text).unwrap();
+        let path_of = |name: &str| {
+            collection
+                .items
+                .iter()
+                .find(|i| i.name == name)
+                .unwrap()
+                .category_path
+                .clone()
+        };
+        assert_eq!(collection.items.len(), 4);
+        assert_eq!(path_of("Mickey Lee"), vec!["Melon", "Watermelon"]);
+        assert_eq!(path_of("Mini, seedless"), vec!["Melon", "Watermelon"]);
+        assert_eq!(path_of("Honeydew"), vec!["Melon"]);
+        assert_eq!(path_of("Tuscan"), vec!["Melon", "Cantaloupe"]);
+    }
+
+    #[test]
+    fn test_parse_placeholder_codes() {
+        let text = "Apple\n• Newvariety (TBD)\n• Othervariety (n/a)\n• Akane (4098)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 3);
+        assert_eq!(collection.items[0].name, "Newvariety");
+        assert!(collection.items[0].pending);
+        assert!(collection.items[0].plu_codes.is_empty());
+        assert_eq!(collection.items[0].category_path, vec!["Apple"]);
+        assert!(collection.items[1].pending);
+        assert!(!collection.items[2].pending);
+    }
+
+    #[test]
+    fn test_running_header_keeps_category() {
+        let text = "Apple
+• Akane (4098)
+Page 3 of 12
+PLU Codes (continued)
+• Alkmene (3000)";
+        let options = ParseOptions {
+            page_marker_pattern: Some(
+                Regex::new(r"^(?:Page \d+ of \d+|PLU Codes \(continued\))$").unwrap(),
+            ),
+            ..Default::default()
+        };
+        let report = parse_plu_report(text, &options).unwrap();
+        assert_eq!(report.collection.items.len(), 2);
+        assert_eq!(report.collection.items[1].name, "Alkmene");
+        assert_eq!(report.collection.items[1].category_path, vec!["Apple"]);
+        assert!(report.stats.unprocessed_lines.is_empty());
+
+        // The default pattern already skips "Page N of M"
+        let default_report = parse_plu_report(
+            "Apple\nPage 3 of 12\n• Alkmene (3000)",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            default_report.collection.items[0].category_path,
+            vec!["Apple"]
+        );
+        assert!(default_report.stats.unprocessed_lines.is_empty());
+
+        // ...and "PLU Codes (continued)" running headers
+        let default_report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(default_report.collection.items[1].name, "Alkmene");
+        assert_eq!(
+            default_report.collection.items[1].category_path,
+            vec!["Apple"]
+        );
+        assert!(default_report.stats.unprocessed_lines.is_empty());
+        assert!(default_report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_nameless_items() {
+        let text = "Apple\n• [red] (4098)";
+        assert_eq!(parse_plu_text(text).unwrap().find_nameless().len(), 1);
+
+        let strict = ParseOptions {
+            recovery: RecoveryMode::Strict,
+            ..Default::default()
+        };
+        assert!(parse_plu_text_with_options(text, &strict).is_err());
+    }
+
+    #[test]
+    fn test_en_and_em_dash_ranges() {
+        let text = "Apple\n• retailer assigned (4193–4217)\n• Misc (3500—3510)\n• Akane (4098)";
+        let collection = parse_plu_text(text).unwrap();
+        // Ranges are not codes: Misc keeps its block as a range-only item
+        assert_eq!(collection.items.len(), 2);
+        assert!(collection.items[0].is_range_only());
+        assert_eq!(
+            collection.items[0].code_ranges,
+            vec![CodeRange {
+                start: 3500,
+                end: 3510
+            }]
+        );
+        assert_eq!(collection.items[1].plu_codes, vec![4098]);
+        assert_eq!(
+            collection.retailer_ranges,
+            vec![CodeRange {
+                start: 4193,
+                end: 4217
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_parenthetical_alias() {
+        let text = "Melon\n• Cantaloupe (Muskmelon) (4049)\n• Kiwano (Horned Melon), small (3333), large (3334)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 3);
+        assert_eq!(collection.items[0].name, "Cantaloupe");
+        assert_eq!(
+            collection.items[0].alternative_name,
+            Some("Muskmelon".to_string())
+        );
+        assert_eq!(collection.items[0].plu_codes, vec![4049]);
+        assert!(collection.items[0].characteristics.is_empty());
+
+        assert_eq!(collection.items[2].name, "Kiwano, large");
+        assert_eq!(
+            collection.items[2].alternative_name,
+            Some("Horned Melon, large".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_plus_joined_components() {
+        let text = "Mixed Vegetables\n• Broccoli + Cauliflower blend (3082)\n• Broccoli (4060)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items[0].name, "Broccoli + Cauliflower blend");
+        assert_eq!(
+            collection.items[0].components,
+            vec!["Broccoli", "Cauliflower"]
+        );
+        assert_eq!(collection.items[0].plu_codes, vec![3082]);
+        assert!(collection.items[1].components.is_empty());
+    }
+
+    #[test]
+    fn test_trademark_symbols_are_stripped() {
+        let text = "Apple\n• Pink Lady® (4130)\n• Jazz™ / Scifresh, small (3434), large (3435)";
+        let collection = parse_plu_text(text).unwrap();
+        let pink = &collection.items[0];
+        assert_eq!(pink.name, "Pink Lady");
+        assert!(pink.trademarked);
+        assert_eq!(pink.plu_codes, vec![4130]);
+
+        let jazz = &collection.items[1];
+        assert_eq!(jazz.name, "Jazz, small");
+        assert_eq!(jazz.alternative_name.as_deref(), Some("Scifresh, small"));
+        assert!(jazz.trademarked);
+        assert!(!parse_plu_text("Apple\n• Akane (4098)").unwrap().items[0].trademarked);
+    }
+
+    #[test]
+    fn test_mixed_varieties_and_sub_groups_under_one_commodity() {
+        let text = "Apple
+• Akane (4098)
+• Cooking Apples:
+  • Bramley (3001)
+  • Heirloom:
+    • Calville Blanc (3002)
+  • Rome (3003)
+• Gala (4133)
+• Dessert Apples:
+  o Honeycrisp (3283)";
+        let collection = parse_plu_text(text).unwrap();
+        let paths: Vec<(&str, Vec<&str>)> = collection
+            .items
+            .iter()
+            .map(|item| {
+                (
+                    item.name.as_str(),
+                    item.category_path.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("Akane", vec!["Apple"]),
+                ("Bramley", vec!["Apple", "Cooking Apples"]),
+                (
+                    "Calville Blanc",
+                    vec!["Apple", "Cooking Apples", "Heirloom"]
+                ),
+                ("Rome", vec!["Apple", "Cooking Apples"]),
+                ("Gala", vec!["Apple"]),
+                ("Honeycrisp", vec!["Apple", "Dessert Apples"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_name_case_option() {
+        let text = "Apple\n• GOLDEN DELICIOUS (4021)\n• golden delicious / honey-crisp USA (4020)";
+        let names = |name_case| {
+            let options = ParseOptions {
+                name_case,
+                ..Default::default()
+            };
+            let collection = parse_plu_text_with_options(text, &options).unwrap();
+            collection
+                .items
+                .into_iter()
+                .map(|item| (item.name, item.alternative_name.unwrap_or_default()))
+                .collect::<Vec<_>>()
+        };
+        let pair = |name: &str, alt: &str| (name.to_string(), alt.to_string());
+
+        assert_eq!(
+            names(NameCase::AsIs),
+            vec![
+                pair("GOLDEN DELICIOUS", ""),
+                pair("golden delicious", "honey-crisp USA")
+            ]
+        );
+        assert_eq!(
+            names(NameCase::Lower),
+            vec![
+                pair("golden delicious", ""),
+                pair("golden delicious", "honey-crisp usa")
+            ]
+        );
+        assert_eq!(
+            names(NameCase::Title),
+            vec![
+                pair("Golden Delicious", ""),
+                pair("Golden Delicious", "Honey-Crisp USA")
+            ]
+        );
+
+        let options = ParseOptions {
+            name_case: NameCase::Lower,
+            ..Default::default()
+        };
+        let collection = parse_plu_text_with_options(text, &options).unwrap();
+        assert_eq!(collection.items[0].category_path, vec!["apple"]);
+
+        let text = "Apple\n• Aurora / Southern Rose / ROSA (3001)";
+        let collection = parse_plu_text_with_options(text, &options).unwrap();
+        assert_eq!(collection.items[0].alternative_names, vec!["rosa"]);
+    }
+
+    #[test]
+    fn test_blank_lines_reset_option() {
+        let text = "Apple\n• Akane (4098)\n\n• Stray (1234)\n\n\n• Lost (4321)";
+
+        // By default blank lines are ignored and both items stay under Apple
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 3);
+        assert_eq!(collection.items[2].category_path, vec!["Apple"]);
+
+        let options = ParseOptions {
+            blank_lines_reset: Some(1),
+            ..Default::default()
+        };
+        let report = parse_plu_report(text, &options).unwrap();
+        assert_eq!(report.collection.items.len(), 1);
+        assert_eq!(report.warnings.len(), 2);
+        assert!(matches!(
+            &report.warnings[0].reason,
+            WarningReason::OrphanItem { content } if content == "Stray (1234)"
+        ));
+
+        // A single blank line is not enough when two are required
+        let options = ParseOptions {
+            blank_lines_reset: Some(2),
+            ..Default::default()
+        };
+        let report = parse_plu_report(text, &options).unwrap();
+        let names: Vec<&str> = report
+            .collection
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Akane", "Stray"]);
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_blend_composition() {
+        let text = "Salads\n• Salad mix (70% romaine, 30% spinach) (4321)\n• Spring mix (baby greens) (4322)";
+        let collection = parse_plu_text(text).unwrap();
+        let salad = &collection.items[0];
+        assert_eq!(salad.name, "Salad mix");
+        assert_eq!(
+            salad.composition,
+            vec![("romaine".to_string(), 70.0), ("spinach".to_string(), 30.0)]
+        );
+        let half = parse_plu_text("Salads\n• Duo (12.5% kale, 87.5% chard) (4323)").unwrap();
+        assert_eq!(
+            half.items[0].composition,
+            vec![("kale".to_string(), 12.5), ("chard".to_string(), 87.5)]
+        );
+        // Oversized percentages are kept as they are instead of overflowing
+        let big = parse_plu_text("Salads\n• Big (42949672.96% kale) (4324)").unwrap();
+        assert_eq!(
+            big.items[0].composition,
+            vec![("kale".to_string(), 42949672.96)]
+        );
+        assert_eq!(salad.plu_codes, vec![4321]);
+        assert!(salad.characteristics.is_empty());
+
+        // Non-percentage parens are still qualifiers
+        let spring = &collection.items[1];
+        assert_eq!(spring.name, "Spring mix (baby greens)");
+        assert!(spring.composition.is_empty());
+        assert_eq!(spring.characteristics, vec!["baby greens"]);
+    }
+
+    #[test]
+    fn test_ripeness_annotations() {
+        let text = "Banana\n• Banana, green (4011)\n• Banana, ripe (4012)\nAvocado\n• Avocado, ripe, small (4225), large (4226)\nGrape\n• Green Grape (4022)";
+        let collection = parse_plu_text(text).unwrap();
+        let green = &collection.items[0];
+        assert_eq!(green.name, "Banana");
+        assert_eq!(green.ripeness.as_deref(), Some("green"));
+        assert_eq!(green.color, None);
+
+        assert_eq!(collection.items[1].ripeness.as_deref(), Some("ripe"));
+
+        let avocado = &collection.items[2];
+        assert_eq!(avocado.name, "Avocado, small");
+        assert_eq!(avocado.ripeness.as_deref(), Some("ripe"));
+        assert_eq!(avocado.size.as_deref(), Some("small"));
+
+        // A leading "Green" is a color, not a ripeness stage
+        let grape = &collection.items[4];
+        assert_eq!(grape.name, "Green Grape");
+        assert_eq!(grape.ripeness, None);
+        assert_eq!(grape.color.as_deref(), Some("green"));
+
+        // Outside ripening commodities a trailing "green" is a color
+        let collection =
+            parse_plu_text("Pepper\n• Bell, green (4065)\n• Bell, ripe (4088)").unwrap();
+        assert_eq!(collection.items[0].name, "Bell, green");
+        assert_eq!(collection.items[0].ripeness, None);
+        assert_eq!(collection.items[1].ripeness.as_deref(), Some("ripe"));
+    }
+
+    #[test]
+    fn test_per_size_aliases() {
+        let text =
+            "Apple\n• Aurora / Southern Rose, small (3001), Nova / Northern Rose, large (3290)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 2);
+        let small = &collection.items[0];
+        assert_eq!(small.name, "Aurora, small");
+        assert_eq!(
+            small.alternative_name.as_deref(),
+            Some("Southern Rose, small")
+        );
+        assert_eq!(small.plu_codes, vec![3001]);
+        let large = &collection.items[1];
+        assert_eq!(large.name, "Nova, large");
+        assert_eq!(
+            large.alternative_name.as_deref(),
+            Some("Northern Rose, large")
+        );
+        assert_eq!(large.plu_codes, vec![3290]);
+    }
+
+    #[test]
+    fn test_semicolon_separated_items() {
+        let text = "Citrus\n• Lime (4048); Lemon (4053)\n• Orange [navel; seedless] (4012)";
+        let collection = parse_plu_text(text).unwrap();
+        let names: Vec<(&str, &[u32])> = collection
+            .items
+            .iter()
+            .map(|item| (item.name.as_str(), item.plu_codes.as_slice()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Lime", &[4048][..]),
+                ("Lemon", &[4053][..]),
+                ("Orange", &[4012][..])
+            ]
+        );
+        assert!(
+            collection.items[..2]
+                .iter()
+                .all(|item| item.category_path == vec!["Citrus"])
+        );
+
+        // A segment that doesn't parse is recorded like a failed line
+        let report =
+            parse_plu_report("Apple\n• Akane; Gala (4133)", &ParseOptions::default()).unwrap();
+        assert_eq!(report.collection.items.len(), 1);
+        assert_eq!(report.collection.items[0].name, "Gala");
+        let unprocessed = &report.stats.unprocessed_lines;
+        assert_eq!(unprocessed.len(), 1);
+        assert_eq!(unprocessed[0].line, "Akane");
+        assert_eq!(unprocessed[0].line_number, 2);
+        assert_eq!(
+            report.warnings[0].reason,
+            WarningReason::UnmatchedLine {
+                content: "Akane".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_trailing_comma_and_conjunction_code_lists() {
+        assert_eq!(parse_plu_codes("(4011, 4012,)"), vec![4011, 4012]);
+        assert_eq!(
+            parse_plu_codes("(4011, 4012, and 4013)"),
+            vec![4011, 4012, 4013]
+        );
+
+        let text = "Banana\n• Cavendish (4011, 4012,)\n• Burro (4010, 4235, and 4236)\n• Plantain (3000 & 3001)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        let codes: Vec<&[u32]> = report
+            .collection
+            .items
+            .iter()
+            .map(|item| item.plu_codes.as_slice())
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                &[4011, 4012][..],
+                &[4010, 4235, 4236][..],
+                &[3000, 3001][..]
+            ]
+        );
+        assert!(report.stats.unprocessed_lines.is_empty());
+    }
+
+    #[test]
+    fn test_footnote_legend_section() {
+        let text = "Apple
+• Golden Delicious, small (4021, 41361,2), large (4020)
+¹ Retailer assigned. ² USA only.";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.footnote_legend.len(), 2);
+        assert_eq!(collection.footnote_legend[&1], "Retailer assigned");
+        assert_eq!(collection.footnote_legend[&2], "USA only");
+        assert_eq!(
+            collection.footnote_meanings(&collection.items[0]),
+            vec!["Retailer assigned", "USA only"]
+        );
+
+        let text = "Banana\n• Cavendish (4011³)\n\nFootnotes:\n3 = Canada only\n4. Seasonal";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 1);
+        assert_eq!(collection.footnote_legend[&3], "Canada only");
+        assert_eq!(collection.footnote_legend[&4], "Seasonal");
+
+        // Items after the legend block are parsed as items again
+        let text = "Banana\n• Cavendish (4011³)\nNotes:\n3 = Canada only\nCitrus\n• Lime (4048)\n1 Lemon (4053)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(report.collection.footnote_legend.len(), 1);
+        let names: Vec<&str> = report
+            .collection
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Cavendish", "Lime"]);
+        assert_eq!(report.collection.items[1].category_path, vec!["Citrus"]);
+        // "1 Lemon (4053)" is reported instead of being read as a legend entry
+        assert_eq!(report.stats.unprocessed_lines.len(), 1);
+        assert_eq!(report.stats.unprocessed_lines[0].line_number, 7);
+        assert_eq!(
+            collection.footnote_meanings(&collection.items[0]),
+            vec!["Canada only"]
+        );
+    }
+
+    #[test]
+    fn test_stray_word_does_not_clobber_category() {
+        // A capitalized word followed by items is still a new top-level category
+        let collection = parse_plu_text("Citrus\n• Lime (4048)\nLemon\n• Eureka (4053)").unwrap();
+        assert_eq!(collection.items[1].category_path, vec!["Lemon"]);
+
+        // Without anything beneath it, "Lemon" is a stray line and Citrus stays current
+        let text = "Citrus\n• Lime (4048)\nLemon\n\nOrange\n• Navel (4012)\nTangerine";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        let paths: Vec<&Vec<String>> = report
+            .collection
+            .items
+            .iter()
+            .map(|item| &item.category_path)
+            .collect();
+        assert_eq!(paths, vec![&vec!["Citrus"], &vec!["Orange"]]);
+        let stray: Vec<(usize, &WarningReason)> = report
+            .warnings
+            .iter()
+            .map(|warning| (warning.line_number, &warning.reason))
+            .collect();
+        assert_eq!(
+            stray,
+            vec![
+                (
+                    3,
+                    &WarningReason::CategoryWithoutItems {
+                        content: "Lemon".into()
+                    }
+                ),
+                (
+                    7,
+                    &WarningReason::CategoryWithoutItems {
+                        content: "Tangerine".into()
+                    }
+                ),
+            ]
+        );
+        assert!(report.stats.unprocessed_lines.is_empty());
+
+        // Filler lines between a category and its items don't hide the items
+        let collection = parse_plu_text("Apple\n42\n• Akane (4098)").unwrap();
+        assert_eq!(collection.items[0].category_path, vec!["Apple"]);
+        let collection =
+            parse_plu_text("Citrus\n• Lime (4048)\nLemon\n42\n• Eureka (4033)").unwrap();
+        assert_eq!(collection.items[1].category_path, vec!["Lemon"]);
+        let report =
+            parse_plu_report("Apple\nApple: Akane (4098)", &ParseOptions::default()).unwrap();
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.collection.items[0].category_path, vec!["Apple"]);
+    }
+
+    #[test]
+    fn test_colon_less_sub_category_header() {
+        let text = "Melon
+• Watermelon
+  o Mickey Lee / Sugarbaby (4331)
+  o Mini, seedless (3421)
+• Honeydew (4034)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        let collection = &report.collection;
+        assert_eq!(collection.items.len(), 3);
+        assert_eq!(
+            collection.items[0].category_path,
+            vec!["Melon", "Watermelon"]
+        );
+        assert_eq!(
+            collection.items[1].category_path,
+            vec!["Melon", "Watermelon"]
+        );
+        assert_eq!(collection.items[2].category_path, vec!["Melon"]);
+        assert!(report.warnings.is_empty());
+        assert!(report.stats.unprocessed_lines.is_empty());
+
+        // A page break between the header and its children doesn't detach them
+        let text = "Melon\n• Watermelon\nPage 3\n42\n  o Mini, seedless (3421)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(
+            collection.items[0].category_path,
+            vec!["Melon", "Watermelon"]
+        );
+    }
+
+    #[test]
+    fn test_bare_page_numbers_are_skipped() {
+        let text = "Apple\n• Akane (4098)\n42\n• Gala (4133)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(report.collection.items.len(), 2);
+        assert_eq!(report.collection.items[1].category_path, vec!["Apple"]);
+        assert!(report.stats.unprocessed_lines.is_empty());
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_inline_sizes_in_code_group() {
+        let text = "Melon\n• Cantaloupe / Muskmelon (small 4049, 43181, large 4050)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 2);
+        let small = &collection.items[0];
+        assert_eq!(small.name, "Cantaloupe, small");
+        assert_eq!(small.alternative_name.as_deref(), Some("Muskmelon, small"));
+        assert_eq!(small.size.as_deref(), Some("small"));
+        assert_eq!(small.plu_codes, vec![4049, 43181]);
+        let large = &collection.items[1];
+        assert_eq!(large.name, "Cantaloupe, large");
+        assert_eq!(large.size.as_deref(), Some("large"));
+        assert_eq!(large.plu_codes, vec![4050]);
+    }
+
+    #[test]
+    fn test_paren_characteristics_before_code_group() {
+        let text = "Grape\n• Grape (seedless) (4022)\n• Globe (seeded, red) (4023)";
+        let collection = parse_plu_text(text).unwrap();
+        let grape = &collection.items[0];
+        assert_eq!(grape.characteristics, vec!["seedless"]);
+        assert_eq!(grape.plu_codes, vec![4022]);
+        assert_eq!(collection.items[1].characteristics, vec!["seeded", "red"]);
+        assert_eq!(collection.items[1].plu_codes, vec![4023]);
+    }
+
+    #[test]
+    fn test_compact_category_and_item_line() {
+        let text = "Melon\n• Honeydew (4034)\nApple: Akane (4098)\n• Gala (4133)";
+        let collection = parse_plu_text(text).unwrap();
+        let paths: Vec<(&str, &[String])> = collection
+            .items
+            .iter()
+            .map(|item| (item.name.as_str(), item.category_path.as_slice()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("Honeydew", &["Melon".to_string()][..]),
+                ("Akane", &["Apple".to_string()][..]),
+                ("Gala", &["Apple".to_string()][..]),
+            ]
+        );
+        assert_eq!(collection.items[1].plu_codes, vec![4098]);
+    }
+
+    #[test]
+    fn test_sub_category_then_direct_item_then_sub_category() {
+        let text = "Melon
+• Watermelon:
+  o Mickey Lee / Sugarbaby (4331)
+  o Mini, seedless (3421)
+• Honeydew (4034)
+• Cantaloupe:
+  o Tuscan (4319)
+• Casaba (4318)
+Apple
+• Akane (4098)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        let paths: Vec<(&str, Vec<&str>)> = report
+            .collection
+            .items
+            .iter()
+            .map(|item| {
+                (
+                    item.name.as_str(),
+                    item.category_path.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("Mickey Lee", vec!["Melon", "Watermelon"]),
+                ("Mini, seedless", vec!["Melon", "Watermelon"]),
+                ("Honeydew", vec!["Melon"]),
+                ("Tuscan", vec!["Melon", "Cantaloupe"]),
+                ("Casaba", vec!["Melon"]),
+                ("Akane", vec!["Apple"]),
+            ]
+        );
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_and_between_size_groups() {
+        for text in [
+            "Plum\n• Plum, small (4040) and large (4042)",
+            "Plum\n• Plum, small (4040) & large (4042)",
+            "Plum\n• Plum, small (4040), and large (4042)",
+        ] {
+            let collection = parse_plu_text(text).unwrap();
+            let items: Vec<(&str, &[u32])> = collection
+                .items
+                .iter()
+                .map(|item| (item.name.as_str(), item.plu_codes.as_slice()))
+                .collect();
+            assert_eq!(
+                items,
+                vec![("Plum, small", &[4040][..]), ("Plum, large", &[4042][..])],
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_trailing_characteristic_after_size_groups() {
+        let text = "Kiwifruit\n• Kiwi, small (4030), large (4301) [fuzzy]";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 2);
+        for (item, (name, code)) in collection
+            .items
+            .iter()
+            .zip([("Kiwi, small", 4030), ("Kiwi, large", 4301)])
+        {
+            assert_eq!(item.name, name);
+            assert_eq!(item.plu_codes, vec![code]);
+            assert_eq!(item.characteristics, vec!["fuzzy"]);
+        }
+    }
+
+    #[test]
+    fn test_leading_zero_and_custom_code_pattern() {
+        let collection = parse_plu_text("Apfel\n• Elstar (0401)").unwrap();
+        let elstar = &collection.items[0];
+        assert_eq!(elstar.plu_codes, vec![401]);
+        assert_eq!(elstar.code_strings, vec!["0401"]);
+
+        // Codes without leading zeros don't duplicate themselves as strings
+        let collection = parse_plu_text("Apple\n• Akane (4098)").unwrap();
+        assert!(collection.items[0].code_strings.is_empty());
+
+        let options = ParseOptions {
+            code_pattern: Some(Regex::new(r"(\d{2})\.(\d{2})").unwrap()),
+            ..Default::default()
+        };
+        let collection =
+            parse_plu_text_with_options("Apfel\n• Boskoop (04.02, 04.03)", &options).unwrap();
+        let boskoop = &collection.items[0];
+        assert_eq!(boskoop.name, "Boskoop");
+        assert_eq!(boskoop.plu_codes, vec![402, 403]);
+        assert_eq!(boskoop.code_strings, vec!["0402", "0403"]);
+    }
+
+    #[test]
+    fn test_code_group_is_last_paren_group() {
+        let text =
+            "Apple\n• Apple (cooking) (4133)\n• Apple (Type A) (4134)\n• Apple (2 lb bag) (4135)";
+        let collection = parse_plu_text(text).unwrap();
+        let items: Vec<(&str, &[u32])> = collection
+            .items
+            .iter()
+            .map(|item| (item.name.as_str(), item.plu_codes.as_slice()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("Apple (cooking)", &[4133][..]),
+                ("Apple", &[4134][..]),
+                ("Apple (2 lb bag)", &[4135][..]),
+            ]
+        );
+        assert_eq!(
+            collection.items[1].alternative_name.as_deref(),
+            Some("Type A")
+        );
+    }
+
+    #[test]
+    fn test_conventional_label_clears_organic() {
+        let text = "Banana\n• Banana, conventional (4011)\n• Banana (conventional) (94011)\n• Plantain, conventional, small (4234), large (4235)";
+        let collection = parse_plu_text(text).unwrap();
+        let banana = &collection.items[0];
+        assert_eq!(banana.name, "Banana");
+        assert!(!banana.is_organic);
+
+        // The label overrides the organic code prefix too
+        let labelled = &collection.items[1];
+        assert_eq!(labelled.name, "Banana");
+        assert!(!labelled.is_organic);
+        assert!(labelled.characteristics.is_empty());
+
+        assert_eq!(collection.items[2].name, "Plantain, small");
+        assert_eq!(collection.items[3].name, "Plantain, large");
+
+        // "organic" only counts as a whole word
+        let collection =
+            parse_plu_text("Apple\n• Inorganic Fuji (4131)\n• Organic Fuji (4129)").unwrap();
+        assert!(!collection.items[0].is_organic);
+        assert!(collection.items[1].is_organic);
+    }
+
+    #[test]
+    fn test_whitespace_only_code_group() {
+        let text = "Misc\n• Foo ( )\n• Bar (4011)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(report.collection.items.len(), 1);
+        assert_eq!(report.collection.items[0].name, "Bar");
+        assert_eq!(report.stats.unprocessed_lines.len(), 1);
+        let unprocessed = &report.stats.unprocessed_lines[0];
+        assert_eq!(unprocessed.line_number, 2);
+        assert_eq!(unprocessed.reason, "empty code group");
+        assert!(parse_plu_codes("( )").is_empty());
+    }
+
+    #[test]
+    fn test_parse_borrowed() {
+        let text = "Apple\n• Akane, small (4098), large (4099)\nMelon\n• Cantaloupe, small (4049)\n• Watermelon:\n  o Mickey Lee / Sugarbaby (4331)";
+        let items = parse_plu_text_borrowed(text);
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].name, "Akane");
+        assert_eq!(items[0].size, Some("small"));
+        assert_eq!(items[1].plu_codes, vec![4099]);
+        assert_eq!(items[2].name, "Cantaloupe");
+        assert_eq!(items[2].size, Some("small"));
+        assert_eq!(items[3].name, "Mickey Lee");
+        assert_eq!(items[3].alternative_name, Some("Sugarbaby"));
+        assert_eq!(items[3].category_path, vec!["Melon", "Watermelon"]);
+
+        // Names and categories are slices of the input, not copies
+        let buffer = text.as_bytes().as_ptr_range();
+        for item in &items {
+            assert!(buffer.contains(&item.name.as_ptr()));
+            for category in &item.category_path {
+                assert!(buffer.contains(&category.as_ptr()));
+            }
+        }
+        assert_eq!(items[3].to_item().name, "Mickey Lee");
+    }
+
+    #[test]
+    fn test_borrowed_items_match_owned() {
+        let text = "Apple\n• Akane, small (4098), large (4099)\nPage 2\n• Aurora / Southern Rose (3001)\nMelon\n• Watermelon\n  o Mickey Lee / Sugarbaby (4331)\n• Cantaloupe, small (4049)\nApple (continued)\n• Alkmene (3000)";
+        let borrowed: Vec<PluItem> = parse_plu_text_borrowed(text)
+            .iter()
+            .map(PluItemRef::to_item)
+            .collect();
+        assert_eq!(borrowed, parse_plu_text(text).unwrap().items);
+        assert_eq!(borrowed[0].name, "Akane, small");
+    }
+
+    #[test]
+    fn test_sold_by_unit() {
+        let text = "Tomato\n• Tomato, per lb (4664)\n• Lime /each (4048)\n• Watermelon [seedless, 3-7 pounds] (3421)\n• Onion per kg, small (4093), large (4094)";
+        let collection = parse_plu_text(text).unwrap();
+        let tomato = &collection.items[0];
+        assert_eq!(tomato.name, "Tomato");
+        assert_eq!(tomato.sold_by.as_deref(), Some("lb"));
+        assert_eq!(tomato.alternative_name, None);
+
+        assert_eq!(collection.items[1].name, "Lime");
+        assert_eq!(collection.items[1].sold_by.as_deref(), Some("each"));
+
+        // A weight range is a characteristic, not a pricing unit
+        let watermelon = &collection.items[2];
+        assert_eq!(watermelon.sold_by, None);
+        assert!(
+            watermelon
+                .characteristics
+                .contains(&"3-7 pounds".to_string())
+        );
+
+        assert_eq!(collection.items[3].name, "Onion, small");
+        assert_eq!(collection.items[4].sold_by.as_deref(), Some("kg"));
+    }
+
+    #[test]
+    fn test_single_space_indented_sub_items() {
+        let text =
+            "Melon\n• Watermelon:\n o Mickey Lee / Sugarbaby (4331)\n o Mini, seedless (3421)";
+        let collection = parse_plu_text(text).unwrap();
+        assert_eq!(collection.items.len(), 2);
+        assert_eq!(collection.items[0].name, "Mickey Lee");
+        assert_eq!(collection.items[1].plu_codes, vec![3421]);
+        for item in &collection.items {
+            assert_eq!(item.category_path, vec!["Melon", "Watermelon"]);
+        }
+        assert_eq!(parse_plu_text_borrowed(text).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_to_channel() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let consumer = std::thread::spawn(move || receiver.iter().count());
+
+        let text = "Apple\n• Akane, small (4098), large (4099)\n• Aurora (3001)".to_string();
+        let stats = parse_plu_to_channel(text, sender).unwrap();
+        assert_eq!(stats.items_parsed, 3);
+        assert_eq!(consumer.join().unwrap(), 3);
+
+        // Items are handed out line by line already in their final form
+        let options = ParseOptions {
+            name_case: NameCase::Lower,
+            ..Default::default()
+        };
+        let mut streamed = Vec::new();
+        let report = parse_plu_report_with(
+            "Apple\n• Akane (4098)\n• Aurora (3001)",
+            &options,
+            |item| streamed.push(item.clone()),
+        )
+        .unwrap();
+        assert_eq!(streamed, report.collection.items);
+        assert_eq!(streamed[0].name, "akane");
+    }
+
+    #[test]
+    fn test_revision_header() {
+        let text = "IFPS PLU Codes 2024\nApple\n• Akane, small (4098), large (4099)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        let collection = &report.collection;
+        assert_eq!(collection.revision.as_deref(), Some("2024"));
+        assert_eq!(collection.items.len(), 2);
+        assert_eq!(collection.items[0].category_path, vec!["Apple"]);
+        assert!(report.stats.unprocessed_lines.is_empty());
+
+        // Without a title there is no revision
+        assert_eq!(
+            parse_plu_text("Apple\n• Aurora (3001)").unwrap().revision,
+            None
+        );
+    }
+
+    #[test]
+    fn test_trailing_punctuation_after_codes() {
+        let text =
+            "Apple\n• Akane, small (4098).\n• Aurora (3001);\n• Fuji, small (4129), large (4131).";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        let items = &report.collection.items;
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].name, "Akane");
+        assert_eq!(items[0].size.as_deref(), Some("small"));
+        assert_eq!(items[0].plu_codes, vec![4098]);
+        assert_eq!(items[1].plu_codes, vec![3001]);
+        assert_eq!(items[3].plu_codes, vec![4131]);
+        assert!(report.stats.unprocessed_lines.is_empty());
+    }
+
+    #[test]
+    fn test_size_count_mismatch_warning() {
+        let text = "Apple\n• Akane, small (4098), large (XX)\n• Aurora, small (3001), large (3290)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(report.collection.items.len(), 3);
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].line_number, 2);
+        assert!(matches!(
+            report.warnings[0].reason,
+            WarningReason::SizeCountMismatch {
+                sizes: 2,
+                items: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("b_melon.txt"),
+            "Melon\n• Watermelon:\n  o Mickey Lee (4331)",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("a_apple.txt"),
+            "Apple\n• Akane, small (4098), large (4099)",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.md"), "Ignored\n• Nope (1111)").unwrap();
+
+        let collection = PluCollection::from_dir(dir.path()).unwrap();
+        let codes: Vec<u32> = collection
+            .items
+            .iter()
+            .flat_map(|i| i.plu_codes.clone())
+            .collect();
+        assert_eq!(codes, vec![4098, 4099, 4331]);
+
+        #[cfg(feature = "rayon")]
+        {
+            let parallel = PluCollection::from_dir_parallel(dir.path()).unwrap();
+            assert_eq!(parallel.items, collection.items);
+        }
+
+        assert!(PluCollection::from_dir(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_multiple_alternative_names() {
+        let text = "Apple\n• Aurora / Southern Rose / Rosa, small (3001)\n• Nova / Northern Rose / Nordic, small (3290), large (3291)";
+        let collection = parse_plu_text(text).unwrap();
+        let aurora = &collection.items[0];
+        assert_eq!(aurora.name, "Aurora");
+        assert_eq!(aurora.size.as_deref(), Some("small"));
+        assert_eq!(aurora.alternative_name.as_deref(), Some("Southern Rose"));
+        assert_eq!(aurora.alternative_names, vec!["Rosa"]);
+
+        let nova_large = &collection.items[2];
+        assert_eq!(nova_large.name, "Nova, large");
+        assert_eq!(
+            nova_large.alternative_name.as_deref(),
+            Some("Northern Rose, large")
+        );
+        assert_eq!(nova_large.alternative_names, vec!["Nordic, large"]);
+    }
+
+    #[test]
+    fn test_trailing_etc_is_stripped() {
+        let collection = parse_plu_text("Berries: strawberry, blueberry, etc. (4240)").unwrap();
+        let berries = &collection.items[0];
+        assert_eq!(berries.name, "strawberry, blueberry");
+        assert_eq!(berries.plu_codes, vec![4240]);
+        assert_eq!(berries.category_path, vec!["Berries"]);
+
+        let collection = parse_plu_text("Citrus\n• Lemon, lime… (4048)").unwrap();
+        assert_eq!(collection.items[0].name, "Lemon, lime");
+        let collection = parse_plu_text("Citrus\n• Orange, tangerine, ... (4012)").unwrap();
+        assert_eq!(collection.items[0].name, "Orange, tangerine");
+    }
+
+    #[test]
+    fn test_continued_category_sections() {
+        let text = "Apple\n• Akane (4098)\nMelon\n• Cantaloupe (4049)\nApple (continued)\n• Aurora (3001)\n• Watermelon (cont'd):\n  o Mickey Lee (4331)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert!(report.stats.unprocessed_lines.is_empty());
+        let collection = report.collection;
+        let apples: Vec<&str> = collection
+            .by_category("Apple")
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(apples, vec!["Akane", "Aurora", "Mickey Lee"]);
+        assert_eq!(
+            collection.items[3].category_path,
+            vec!["Apple", "Watermelon"]
+        );
+
+        // A "(continued)" line for a category never opened is a running header
+        let text = "Apple\n• Akane (4098)\nStone Fruit (continued)\n• Aurora (3001)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(report.collection.items[1].category_path, vec!["Apple"]);
+        assert!(report.collection.by_category("Stone Fruit").is_empty());
+    }
+
+    #[test]
+    fn test_slash_separated_codes() {
+        let text = "Citrus\n• Lemon (4053/4958)\n• Lime, small (4048 / 4305), large (3626)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert!(report.stats.unprocessed_lines.is_empty());
+        let items = &report.collection.items;
+        assert_eq!(items[0].name, "Lemon");
+        assert_eq!(items[0].plu_codes, vec![4053, 4958]);
+        assert_eq!(items[0].alternative_name, None);
+        assert_eq!(items[1].plu_codes, vec![4048, 4305]);
+        assert_eq!(items[2].plu_codes, vec![3626]);
+    }
+
+    #[test]
+    fn test_o_items_under_nested_headers() {
+        let text = "Apple\n• Cooking:\n  • Heirloom:\n    o Calville (3350)\n  o Bramley (3349)\n• Fuji (4131)";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert!(report.warnings.is_empty());
+        let paths: Vec<(&str, Vec<String>)> = report
+            .collection
+            .items
+            .iter()
+            .map(|item| (item.name.as_str(), item.category_path.clone()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (
+                    "Calville",
+                    vec!["Apple".into(), "Cooking".into(), "Heirloom".into()]
+                ),
+                ("Bramley", vec!["Apple".into(), "Cooking".into()]),
+                ("Fuji", vec!["Apple".into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skipped_lines_are_warnings() {
+        let text = "Apple\n• Akane (4098)\n[seedless]\nsomething odd here";
+        let report = parse_plu_report(text, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            report.warnings,
+            vec![
+                ParseWarning {
+                    line_number: 3,
+                    reason: WarningReason::DetachedCharacteristics {
+                        content: "[seedless]".into()
+                    },
+                },
+                ParseWarning {
+                    line_number: 4,
+                    reason: WarningReason::UnmatchedLine {
+                        content: "something odd here".into()
+                    },
+                },
+            ]
+        );
+        assert_eq!(report.stats.unprocessed_lines.len(), 1);
+    }
 }
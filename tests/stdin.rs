@@ -0,0 +1,34 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Runs the binary with `args`, feeding `input` through stdin, and returns its stdout
+fn run_with_stdin(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_plus"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start the binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_reads_piped_stdin() {
+    let input = "Apple\n• Akane, small (4098), large (4099)\n• Gala (4133)\n";
+    let stdout = run_with_stdin(&[], input);
+    assert!(stdout.contains("Successfully parsed 3 PLU items."));
+    assert!(stdout.contains("Found item: PluItem { name: \"Akane, small\""));
+
+    // "-" asks for stdin explicitly
+    let stdout = run_with_stdin(&["-"], input);
+    assert!(stdout.contains("Successfully parsed 3 PLU items."));
+}